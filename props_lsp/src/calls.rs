@@ -0,0 +1,142 @@
+use props_parser::nodes::Span;
+use props_parser::tokens::Token;
+
+/**
+ * A whitespace delimited call, e.g. `f a (g b) c`, found in the tokens of a line
+ **/
+#[derive(Debug)]
+pub(crate) struct Call {
+    pub callee: Span,
+    // a method accessed on a receiver, which is its first parameter
+    pub method: bool,
+    // the first token of each argument
+    pub args: Vec<Span>,
+    // the argument being written if the tokens end within the call
+    pub open: Option<usize>,
+}
+
+/**
+ * Finds the calls in the tokens of a line, the way the parser delimits arguments:
+ * whitespace separates them unless it surrounds a binary operator, and calls in an argument must be parenthesized
+ **/
+pub(crate) fn calls(tokens: &[(Token, Span)]) -> Vec<Call> {
+    let end = tokens.iter()
+        .position(|(token, _)| token == &Token::Newline)
+        .unwrap_or(tokens.len());
+    let mut calls = vec![];
+    scan(&tokens[..end], &mut calls);
+    calls
+}
+
+fn scan(tokens: &[(Token, Span)], calls: &mut Vec<Call>) {
+    let mut i = 0;
+
+    while i < tokens.len() {
+        match &tokens[i].0 {
+            Token::Ident(_) if matches!(tokens.get(i + 1), Some((Token::Whitespace, _))) && tokens.get(i + 2).is_none_or(|(token, _)| starts_argument(token)) => {
+                i = call(tokens, i, calls);
+            }
+            Token::ParenthOpen => i = parenthesized(tokens, i, calls) + 1,
+            _ => i += 1,
+        }
+    }
+}
+
+/**
+ * Reads the call whose callee is at an index, returning the index of the first token after it
+ **/
+fn call(tokens: &[(Token, Span)], callee: usize, calls: &mut Vec<Call>) -> usize {
+    let mut args = vec![];
+    let mut open = None;
+    let mut i = callee + 2;
+
+    'args: loop {
+        match tokens.get(i) {
+            Some((token, span)) if starts_argument(token) => args.push(*span),
+            Some(_) => break,
+            None => {
+                open = Some(args.len());
+                break;
+            }
+        }
+
+        loop {
+            match tokens.get(i) {
+                None => {
+                    open = Some(args.len() - 1);
+                    break 'args;
+                }
+                // a function literal runs to the end of the line
+                Some((Token::Pipe | Token::FuncOpen, _)) => {
+                    i = tokens.len();
+                    open = Some(args.len() - 1);
+                    break 'args;
+                }
+                Some((Token::ParenthOpen, _)) => i = parenthesized(tokens, i, calls) + 1,
+                Some((Token::Whitespace, _)) => {
+                    let around_operator = is_binary_operator(&tokens[i - 1].0) || tokens.get(i + 1).is_some_and(|(token, _)| is_binary_operator(token));
+                    i += 1;
+
+                    if !around_operator {
+                        continue 'args;
+                    }
+                }
+                Some((Token::ParenthClose | Token::Comma | Token::FuncClose | Token::Assignment, _)) => break 'args,
+                Some(_) => i += 1,
+            }
+        }
+    }
+
+    calls.push(Call {
+        callee: tokens[callee].1,
+        method: callee > 0 && matches!(tokens[callee - 1].0, Token::Period | Token::SafeAccess),
+        args,
+        open,
+    });
+
+    i
+}
+
+/**
+ * Finds the calls inside the parenthesis at an index, returning the index of the one closing it.
+ * Calls inside a closed parenthesis are finished, even if its tokens end within them
+ **/
+fn parenthesized(tokens: &[(Token, Span)], open: usize, calls: &mut Vec<Call>) -> usize {
+    let close = closing(tokens, open);
+    let first = calls.len();
+    scan(&tokens[open + 1..close], calls);
+
+    if close < tokens.len() {
+        calls[first..].iter_mut().for_each(|call| call.open = None);
+    }
+
+    close
+}
+
+/**
+ * The index of the parenthesis closing the one at an index, or the end of the tokens if it is not closed
+ **/
+fn closing(tokens: &[(Token, Span)], open: usize) -> usize {
+    let mut depth = 0;
+
+    for (i, (token, _)) in tokens.iter().enumerate().skip(open) {
+        match token {
+            Token::ParenthOpen => depth += 1,
+            Token::ParenthClose if depth == 1 => return i,
+            Token::ParenthClose => depth -= 1,
+            _ => {}
+        }
+    }
+
+    tokens.len()
+}
+
+fn starts_argument(token: &Token) -> bool {
+    matches!(token, Token::Ident(_) | Token::Number(_) | Token::StringLiteral(_) | Token::CharLiteral(_) |
+        Token::ParenthOpen | Token::Pipe | Token::FuncOpen | Token::None | Token::Not)
+}
+
+fn is_binary_operator(token: &Token) -> bool {
+    matches!(token, Token::Addition | Token::Subtraction | Token::Multiplication | Token::Division | Token::Mod | Token::Power |
+        Token::Equality | Token::GreaterThan | Token::LessThan | Token::GreaterEqual | Token::LessEqual | Token::Coalesce | Token::As)
+}
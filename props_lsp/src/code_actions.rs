@@ -0,0 +1,283 @@
+use std::collections::HashMap;
+use lsp_types::{CodeAction, CodeActionKind, CodeActionOrCommand, Diagnostic, Position, Range, TextEdit, Uri, WorkspaceEdit};
+use props_parser::error::ParserErr;
+use props_parser::lexer::Lexer;
+use props_parser::nodes::{AstNode, Expression, Identifier, MathExpr, Span};
+use props_parser::symbols::SymbolKind;
+use props_parser::tokens::Token;
+use props_parser::types::{PrimitiveType, Type};
+use crate::calls::calls;
+use crate::document::Document;
+
+/**
+ * The fixes for the diagnostics on the lines of a range, and annotations for the bindings declared there
+ **/
+pub(crate) fn code_actions(document: &Document, uri: &Uri, range: Range) -> Vec<CodeActionOrCommand> {
+    let on_lines = |span: Span| range.start.line as usize <= span.line && span.line <= range.end.line as usize;
+    let mut actions = vec![];
+
+    for err in document.errs().iter().filter(|err| on_lines(err.span())) {
+        let diagnostic = document.diagnostic(err);
+
+        match err {
+            ParserErr::UnmatchedTypes { type_1, type_2, span } => {
+                let Some((annotation, value)) = annotated_assignment(document, *span) else {
+                    continue;
+                };
+
+                if type_1.map(Type::is_known) {
+                    actions.push(quick_fix(uri, format!("Change annotation to `{}`", type_1), &diagnostic, vec![
+                        TextEdit::new(document.range(annotation), type_1.to_string()),
+                    ]));
+                }
+
+                if let Some(value) = value {
+                    actions.extend(cast(document, uri, &diagnostic, value, type_1.map(Clone::clone), type_2.map(Clone::clone)));
+                }
+            }
+            ParserErr::ReassignedType { expected, found, span, .. } => {
+                if let Some(value) = assigned_value(&line_tokens(document, span.line), *span) {
+                    actions.extend(cast(document, uri, &diagnostic, value, found.map(Clone::clone), expected.map(Clone::clone)));
+                }
+            }
+            ParserErr::UnmatchedArgument { expected, found, span } => {
+                // only an argument which is a single token is known to span the whole argument
+                let is_argument = calls(&line_tokens(document, span.line)).iter().any(|call| call.args.contains(span));
+                if is_argument {
+                    actions.extend(cast(document, uri, &diagnostic, *span, found.map(Clone::clone), expected.map(Clone::clone)));
+                }
+            }
+            ParserErr::UndefinedBinding { name, span } => {
+                let Some(declaration) = declaration(document, *span) else {
+                    continue;
+                };
+
+                let line = document.line(span.line);
+                let indent = &line[..line.len() - line.trim_start().len()];
+
+                actions.push(quick_fix(uri, format!("Create binding `{}`", name), &diagnostic, vec![
+                    TextEdit::new(Range::new(Position::new(span.line as u32, 0), Position::new(span.line as u32, 0)), format!("{}{}{}\n", indent, name, declaration)),
+                ]));
+            }
+            _ => {}
+        }
+    }
+
+    let symbols = document.symbols();
+    for id in symbols.unused() {
+        let symbol = symbols.symbol(id);
+        let Some(last_line) = on_lines(symbol.span).then(|| statement_end(document.ast(), symbol.span)).flatten() else {
+            continue;
+        };
+
+        actions.push(quick_fix(uri, format!("Remove unused binding `{}`", symbol.name), &document.unused_diagnostic(symbol), vec![
+            TextEdit::new(Range::new(Position::new(symbol.span.line as u32, 0), Position::new(last_line as u32 + 1, 0)), String::new()),
+        ]));
+    }
+
+    for symbol in symbols.symbols().iter().filter(|symbol| symbol.kind == SymbolKind::Variable && on_lines(symbol.span)) {
+        let annotated = line_tokens(document, symbol.span.line).iter()
+            .skip_while(|(_, span)| *span != symbol.span)
+            .nth(1)
+            .is_none_or(|(token, _)| token == &Token::TypeAnnotator);
+
+        if !annotated && symbol.type_.is_known() {
+            actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                title: format!("Add type annotation `{}`", symbol.type_),
+                kind: Some(CodeActionKind::REFACTOR_REWRITE),
+                edit: Some(edit(uri, vec![TextEdit::new(document.range(Span::new(symbol.span.line, symbol.span.end, symbol.span.end)), format!(": {}", symbol.type_))])),
+                ..Default::default()
+            }));
+        }
+    }
+
+    actions
+}
+
+/**
+ * The rest of the declaration of a binding used at a span, with a value which type checks where it is used.
+ * None if the use expects a type without a literal to stand in for it
+ **/
+fn declaration(document: &Document, use_: Span) -> Option<String> {
+    let tokens = line_tokens(document, use_.line);
+    let index = tokens.iter().position(|(_, span)| *span == use_)?;
+
+    // a number literal takes the type of the other operand
+    let is_operator = |token: &Token| matches!(token,
+        Token::Addition | Token::Subtraction | Token::Multiplication | Token::Division | Token::Mod | Token::Power |
+        Token::GreaterThan | Token::LessThan | Token::GreaterEqual | Token::LessEqual | Token::Equality
+    );
+    let before = tokens[..index].iter().rev().find(|(token, _)| !token.is_insignificant());
+    let after = tokens[index + 1..].iter().find(|(token, _)| !token.is_insignificant());
+    if [before, after].into_iter().flatten().any(|(token, _)| is_operator(token)) {
+        return Some(" = 0".to_string());
+    }
+
+    let Some(expected) = expected_type(document, &tokens, use_) else {
+        return Some(" = none".to_string());
+    };
+
+    Some(format!(": {} = {}", expected, placeholder(&expected)?))
+}
+
+/**
+ * The type the value at a span is expected to have, as an argument or the value of an annotated assignment
+ **/
+fn expected_type(document: &Document, tokens: &[(Token, Span)], value: Span) -> Option<Type> {
+    let symbols = document.symbols();
+
+    if let Some(call) = calls(tokens).into_iter().find(|call| call.args.contains(&value)) {
+        let callee = symbols.symbol(symbols.symbol_at(call.callee.line, call.callee.start)?);
+        let Type::Function(params, _, _) = callee.annotation.as_ref().unwrap_or(&callee.type_).unalias() else {
+            return None;
+        };
+
+        // a method's receiver is the value it is accessed on
+        let index = call.args.iter().position(|arg| *arg == value)? + call.method as usize;
+        return params.get(index).map(|param| param.map(Clone::clone)).filter(Type::is_known);
+    }
+
+    symbols.symbols().iter()
+        .filter(|symbol| symbol.span.line == value.line && assigned_value(tokens, symbol.span) == Some(value))
+        .find_map(|symbol| symbol.annotation.clone())
+        .filter(Type::is_known)
+}
+
+/**
+ * A literal of a type, if it has one
+ **/
+fn placeholder(type_: &Type) -> Option<String> {
+    match type_.unalias() {
+        Type::Optional(_) => Some("none".to_string()),
+        Type::Primitive(PrimitiveType::Str) => Some("\"\"".to_string()),
+        Type::Primitive(PrimitiveType::Char) => Some("' '".to_string()),
+        Type::Primitive(_) => Some("0".to_string()),
+        Type::Compound(types) => types.iter()
+            .map(|type_| type_.map(placeholder))
+            .collect::<Option<Vec<String>>>()
+            .map(|values| format!("({})", values.join(", "))),
+        _ => None,
+    }
+}
+
+/**
+ * Casts a value to the expected type if `as` converts between the types
+ **/
+fn cast(document: &Document, uri: &Uri, diagnostic: &Diagnostic, value: Span, from: Type, to: Type) -> Option<CodeActionOrCommand> {
+    if from == Type::Undefined || !matches!(to.unalias(), Type::Primitive(_)) || !from.castable_to(&to) {
+        return None;
+    }
+
+    let single_token = line_tokens(document, value.line).iter().any(|(_, span)| *span == value);
+    let edits = if single_token {
+        vec![TextEdit::new(document.range(Span::new(value.line, value.end, value.end)), format!(" as {}", to))]
+    } else {
+        // `as` binds tighter than the operators of the value
+        vec![
+            TextEdit::new(document.range(Span::new(value.line, value.start, value.start)), "(".to_string()),
+            TextEdit::new(document.range(Span::new(value.line, value.end, value.end)), format!(") as {}", to)),
+        ]
+    };
+
+    Some(quick_fix(uri, format!("Cast to `{}`", to), diagnostic, edits))
+}
+
+/**
+ * The annotation and the value of an assignment like `x: I32 = value` declaring the identifier at a span.
+ * The value is left out if it is a function literal or runs over several lines
+ **/
+fn annotated_assignment(document: &Document, ident: Span) -> Option<(Span, Option<Span>)> {
+    let tokens = line_tokens(document, ident.line);
+    let start = tokens.iter().position(|(_, span)| *span == ident)?;
+
+    if tokens.get(start + 1).map(|(token, _)| token) != Some(&Token::TypeAnnotator) {
+        return None;
+    }
+
+    let end = start + tokens[start..].iter().position(|(token, _)| token == &Token::Assignment)?;
+    let annotation: Vec<Span> = tokens[start + 2..end].iter()
+        .filter(|(token, _)| token != &Token::Whitespace)
+        .map(|(_, span)| *span)
+        .collect();
+
+    Some((annotation.first()?.to(*annotation.last()?), assigned_value(&tokens, ident)))
+}
+
+/**
+ * The value assigned on the rest of the line to the identifier at a span
+ **/
+fn assigned_value(tokens: &[(Token, Span)], ident: Span) -> Option<Span> {
+    let value: Vec<&(Token, Span)> = tokens.iter()
+        .skip_while(|(_, span)| *span != ident)
+        .skip_while(|(token, _)| token != &Token::Assignment)
+        .skip(1)
+        .filter(|(token, _)| !token.is_insignificant())
+        .collect();
+
+    if value.iter().any(|(token, _)| matches!(token, Token::Pipe | Token::FuncOpen)) {
+        return None;
+    }
+
+    Some(value.first()?.1.to(value.last()?.1))
+}
+
+/**
+ * The last line of the assignment declaring the identifier at a span, if it binds nothing else
+ **/
+fn statement_end(nodes: &[AstNode], ident: Span) -> Option<usize> {
+    nodes.iter().find_map(|node| match node {
+        AstNode::Assignment(Identifier::Identifier(_, _, span), expr) if *span == ident => Some(last_line(expr, span.line)),
+        AstNode::Assignment(Identifier::Mutable(inner), expr) if matches!(inner.as_ref(), Identifier::Identifier(_, _, span) if *span == ident) => Some(last_line(expr, ident.line)),
+        AstNode::Assignment(_, expr) | AstNode::Return(expr, _) | AstNode::Expression(expr) => nested_statement_end(expr, ident),
+        AstNode::ImpFuncCall(_, args) => args.iter().find_map(|arg| nested_statement_end(arg, ident)),
+        AstNode::Impl(_, _, methods, _) => statement_end(methods, ident),
+        _ => None,
+    })
+}
+
+fn nested_statement_end(expr: &Expression, ident: Span) -> Option<usize> {
+    match expr {
+        Expression::FuncLiteral { statements, .. } => statement_end(statements, ident),
+        Expression::Compound(exprs) |
+        Expression::MathExpr(MathExpr::FuncCall(_, exprs)) => exprs.iter().find_map(|expr| nested_statement_end(expr, ident)),
+        Expression::MathExpr(MathExpr::Coalesce(_, rhs)) => nested_statement_end(rhs, ident),
+        _ => None,
+    }
+}
+
+/**
+ * The line an expression starting on a line ends on, which is later only for function literals with a body
+ **/
+fn last_line(expr: &Expression, line: usize) -> usize {
+    match expr {
+        Expression::FuncLiteral { end, .. } => end.line,
+        Expression::Compound(exprs) |
+        Expression::MathExpr(MathExpr::FuncCall(_, exprs)) => exprs.iter().map(|expr| last_line(expr, line)).max().unwrap_or(line),
+        Expression::MathExpr(MathExpr::Coalesce(_, rhs)) => last_line(rhs, line),
+        _ => line,
+    }
+}
+
+/**
+ * The tokens of a single line, with spans placed on that line
+ **/
+fn line_tokens(document: &Document, line: usize) -> Vec<(Token, Span)> {
+    Lexer::lex(&document.line(line))
+        .into_iter()
+        .map(|(token, span)| (token, Span { line, ..span }))
+        .collect()
+}
+
+fn quick_fix(uri: &Uri, title: String, diagnostic: &Diagnostic, edits: Vec<TextEdit>) -> CodeActionOrCommand {
+    CodeActionOrCommand::CodeAction(CodeAction {
+        title,
+        kind: Some(CodeActionKind::QUICKFIX),
+        diagnostics: Some(vec![diagnostic.clone()]),
+        edit: Some(edit(uri, edits)),
+        ..Default::default()
+    })
+}
+
+fn edit(uri: &Uri, edits: Vec<TextEdit>) -> WorkspaceEdit {
+    WorkspaceEdit::new(HashMap::from([(uri.clone(), edits)]))
+}
@@ -0,0 +1,161 @@
+use lsp_types::{CompletionItem, CompletionItemKind, CompletionResponse, Documentation, Position};
+use props_parser::symbols::{Symbol, SymbolKind};
+use props_parser::types::{Type, PRIM_TYPES};
+use crate::document::Document;
+
+const KEYWORDS: [&str; 9] = ["return", "mut", "none", "impure", "type", "trait", "impl", "for", "as"];
+
+pub(crate) fn completion(document: &Document, position: Position) -> Option<CompletionResponse> {
+    let (line, offset) = document.offset(position);
+    let text = &document.line(line)[..offset];
+
+    // the word being typed is left for the editor to filter by
+    let word_start = text.trim_end_matches(|c: char| c.is_ascii_alphanumeric() || c == '_').len();
+    let before = text[..word_start].trim_end();
+
+    let items = if before.ends_with('.') {
+        members(document, line, before.trim_end_matches('.').trim_end_matches('?').len())
+    } else if before.ends_with(':') || before.ends_with("->") || before.ends_with(" as") {
+        types(document, line, offset)
+    } else {
+        values(document, line, offset)
+    };
+
+    Some(CompletionResponse::Array(items))
+}
+
+/**
+ * The methods which can be accessed on the value ending where the `.` or `?.` starts
+ **/
+fn members(document: &Document, line: usize, receiver_end: usize) -> Vec<CompletionItem> {
+    let symbols = document.symbols();
+    let Some((_, receiver)) = receiver_end.checked_sub(1).and_then(|pos| symbols.type_at(line, pos)) else {
+        return vec![];
+    };
+
+    // `?.` accesses the value inside an optional
+    let receiver = match receiver.unalias() {
+        Type::Optional(inner) => inner.as_ref(),
+        _ => receiver,
+    };
+
+    symbols.symbols()
+        .iter()
+        .filter(|symbol| symbol.kind == SymbolKind::Method)
+        .filter(|symbol| match &symbol.type_ {
+            // the receiver is the first argument of a method
+            Type::Function(params, _, _) => params.first().is_some_and(|param| param.map(|param| param == receiver)),
+            _ => false,
+        })
+        .map(|symbol| item(symbol, CompletionItemKind::METHOD, "1"))
+        .collect()
+}
+
+fn types(document: &Document, line: usize, offset: usize) -> Vec<CompletionItem> {
+    let mut names: Vec<&str> = PRIM_TYPES.keys()
+        .map(String::as_str)
+        .chain(["Unit", "Option", "Result"])
+        .collect();
+    names.sort();
+
+    let declared = document.symbols()
+        .visible_at(line, offset)
+        .into_iter()
+        .filter(|symbol| symbol.kind == SymbolKind::Type)
+        .map(|symbol| item(symbol, CompletionItemKind::STRUCT, "0"));
+
+    names.into_iter()
+        .map(|name| CompletionItem {
+            label: name.to_string(),
+            kind: Some(CompletionItemKind::STRUCT),
+            sort_text: Some(format!("1{}", name)),
+            ..Default::default()
+        })
+        .chain(declared)
+        .collect()
+}
+
+/**
+ * The bindings in scope followed by keywords, bindings which fit the type expected at the position first
+ **/
+fn values(document: &Document, line: usize, offset: usize) -> Vec<CompletionItem> {
+    let visible = document.symbols().visible_at(line, offset);
+    let expected = expected_type(document, &visible, line, offset);
+
+    let bindings = visible.iter()
+        .filter(|symbol| matches!(symbol.kind, SymbolKind::Variable | SymbolKind::Parameter))
+        .map(|symbol| {
+            let type_ = symbol.annotation.as_ref().unwrap_or(&symbol.type_);
+            let fits = expected.as_ref().is_some_and(|expected| type_ != &Type::Undefined && type_.assignable_to(expected));
+            let kind = match type_ {
+                Type::Function(..) => CompletionItemKind::FUNCTION,
+                _ => CompletionItemKind::VARIABLE,
+            };
+
+            item(symbol, kind, if fits { "0" } else { "1" })
+        });
+
+    let keywords = KEYWORDS.iter().map(|keyword| CompletionItem {
+        label: keyword.to_string(),
+        kind: Some(CompletionItemKind::KEYWORD),
+        sort_text: Some(format!("2{}", keyword)),
+        ..Default::default()
+    });
+
+    bindings.chain(keywords).collect()
+}
+
+/**
+ * The type of the value being assigned at a position, from the annotation or the binding being reassigned.
+ * Read from the text as the statement being typed usually does not parse yet
+ **/
+fn expected_type(document: &Document, visible: &[&Symbol], line: usize, offset: usize) -> Option<Type> {
+    let text = &document.line(line)[..offset];
+    let assignment = text.match_indices('=')
+        .map(|(i, _)| i)
+        .find(|i| !text[..*i].ends_with(['=', '<', '>', '!']) && !text[i + 1..].starts_with('='))?;
+
+    let target = text[..assignment].trim();
+    let target = target.strip_prefix("mut ").unwrap_or(target);
+    let (name, annotation) = match target.split_once(':') {
+        Some((name, annotation)) => (name.trim(), Some(annotation.trim())),
+        None => (target, None),
+    };
+
+    match annotation {
+        Some(annotation) => match annotation.strip_suffix('?') {
+            Some(inner) => resolve(inner, visible).map(|t| Type::Optional(Box::new(t))),
+            None => resolve(annotation, visible),
+        },
+        None => visible.iter()
+            .rev()
+            .find(|symbol| symbol.name == name && symbol.mutable)
+            .map(|symbol| symbol.annotation.clone().unwrap_or(symbol.type_.clone())),
+    }
+}
+
+/**
+ * A type written as a single name, either a declared type or a builtin one
+ **/
+fn resolve(name: &str, visible: &[&Symbol]) -> Option<Type> {
+    let declared = visible.iter().rev().find(|symbol| symbol.kind == SymbolKind::Type && symbol.name == name);
+    if let Some(symbol) = declared {
+        return Some(symbol.type_.clone());
+    }
+
+    match Type::from(name.to_string()) {
+        Type::Defined(..) => None,
+        type_ => Some(type_),
+    }
+}
+
+fn item(symbol: &Symbol, kind: CompletionItemKind, rank: &str) -> CompletionItem {
+    CompletionItem {
+        label: symbol.name.clone(),
+        kind: Some(kind),
+        detail: Some(symbol.annotation.as_ref().unwrap_or(&symbol.type_).to_string()),
+        documentation: symbol.doc.clone().map(Documentation::String),
+        sort_text: Some(format!("{}{}", rank, symbol.name)),
+        ..Default::default()
+    }
+}
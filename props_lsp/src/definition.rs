@@ -0,0 +1,27 @@
+use lsp_types::{GotoDefinitionResponse, Location, Position, Uri};
+use crate::document::Document;
+
+/**
+ * Where the symbol at a position is declared, i.e. the assignment, parameter or method that first binds it
+ **/
+pub(crate) fn definition(document: &Document, uri: &Uri, position: Position) -> Option<GotoDefinitionResponse> {
+    let (line, offset) = document.offset(position);
+    let symbols = document.symbols();
+    let symbol = symbols.symbol(symbols.symbol_at(line, offset)?);
+
+    Some(GotoDefinitionResponse::Scalar(Location::new(uri.clone(), document.range(symbol.span))))
+}
+
+pub(crate) fn references(document: &Document, uri: &Uri, position: Position, include_declaration: bool) -> Option<Vec<Location>> {
+    let (line, offset) = document.offset(position);
+    let symbols = document.symbols();
+    let id = symbols.symbol_at(line, offset)?;
+
+    let declaration = Some(symbols.symbol(id).span).filter(|_| include_declaration);
+    let mut spans: Vec<_> = declaration.into_iter()
+        .chain(symbols.references(id))
+        .collect();
+    spans.sort_by_key(|span| (span.line, span.start));
+
+    Some(spans.into_iter().map(|span| Location::new(uri.clone(), document.range(span))).collect())
+}
@@ -0,0 +1,223 @@
+use std::borrow::Cow;
+use lsp_types::{Diagnostic, DiagnosticSeverity, DiagnosticTag, Position, Range, TextDocumentContentChangeEvent};
+use props_parser::error::ParserErr;
+use props_parser::lexer::Lexer;
+use props_parser::nodes::{AstNode, Span};
+use props_parser::pipeline::PropsPipeline;
+use props_parser::symbols::{Symbol, SymbolTable};
+use props_parser::tokens::Token;
+use ropey::Rope;
+
+/**
+ * An open source file with the result of parsing and typing it
+ **/
+pub struct Document {
+    text: Rope,
+    pipeline: PropsPipeline,
+    ast: Vec<AstNode>,
+    errs: Vec<ParserErr>,
+    // the tokens of each line with comments, lexed again only when the line is edited
+    tokens: Vec<Vec<(Token, Span)>>,
+}
+
+impl Document {
+    pub fn new(text: String) -> Self {
+        let mut document = Document {
+            text: Rope::new(),
+            pipeline: PropsPipeline::default(),
+            ast: vec![],
+            errs: vec![],
+            tokens: vec![],
+        };
+
+        document.update(text);
+        document
+    }
+
+    pub fn update(&mut self, text: String) {
+        self.text = Rope::from_str(&text);
+        self.tokens = text.lines().enumerate().map(|(line_num, line)| Lexer::lex_line_with_comments(line, line_num)).collect();
+        (self.ast, self.errs) = self.pipeline.parse(text);
+    }
+
+    /**
+     * Applies the edits of a change notification in order, then parses again only around the lines they replaced
+     **/
+    pub fn change(&mut self, changes: Vec<TextDocumentContentChangeEvent>) {
+        // the first line edited, the end of the lines replaced as they were before the edits and the end of the lines replacing them
+        let mut replaced: Option<(usize, usize, usize)> = None;
+
+        for change in changes {
+            let Some(range) = change.range else {
+                self.update(change.text);
+                replaced = None;
+                continue;
+            };
+
+            let start = self.char_index(range.start);
+            let end = self.char_index(range.end).max(start);
+            let (first, last) = (self.text.char_to_line(start), self.text.char_to_line(end));
+            let old_end = (last + 1).min(self.line_count());
+
+            self.text.remove(start..end);
+            self.text.insert(start, &change.text);
+            let new_end = (self.text.char_to_line(start + change.text.chars().count()) + 1).min(self.line_count());
+
+            replaced = Some(match replaced {
+                None => (first, old_end, new_end),
+                // lines after the ones replaced before moved with that edit, and those after this edit move with it
+                Some((first_before, old_end_before, new_end_before)) => {
+                    let end = new_end_before.max(old_end);
+                    (first_before.min(first), end - new_end_before + old_end_before, end - old_end + new_end)
+                }
+            });
+        }
+
+        if let Some((first, old_end, new_end)) = replaced {
+            let lines: Vec<String> = (first..new_end).map(|line| self.line(line).into_owned()).collect();
+
+            let tokens = lines.iter().enumerate().map(|(i, line)| Lexer::lex_line_with_comments(line, first + i));
+            self.tokens.splice(first..old_end, tokens);
+            // the lines after the edited ones moved with them
+            for (line_num, tokens) in self.tokens.iter_mut().enumerate().skip(new_end) {
+                tokens.iter_mut().for_each(|(_, span)| span.line = line_num);
+            }
+
+            self.pipeline.reparse(first..old_end, lines, &mut self.ast, &mut self.errs);
+        }
+    }
+
+    pub fn text(&self) -> String {
+        self.text.to_string()
+    }
+
+    pub fn ast(&self) -> &[AstNode] {
+        &self.ast
+    }
+
+    pub fn errs(&self) -> &[ParserErr] {
+        &self.errs
+    }
+
+    /**
+     * The tokens of the text including comments, as `Lexer::lex_with_comments` lexes it
+     **/
+    pub fn tokens(&self) -> impl Iterator<Item = &(Token, Span)> {
+        self.tokens.iter().flatten()
+    }
+
+    pub fn symbols(&self) -> &SymbolTable {
+        self.pipeline.symbols()
+    }
+
+    /**
+     * A line without its line break, split the way `str::lines` splits the text for the parser
+     **/
+    pub fn line(&self, line: usize) -> Cow<'_, str> {
+        if line >= self.line_count() {
+            return Cow::Borrowed("");
+        }
+
+        let strip = |text: &str| -> usize {
+            match text.strip_suffix('\n') {
+                Some(text) => text.strip_suffix('\r').unwrap_or(text).len(),
+                None => text.len(),
+            }
+        };
+
+        match Cow::from(self.text.line(line)) {
+            Cow::Borrowed(text) => Cow::Borrowed(&text[..strip(text)]),
+            Cow::Owned(mut text) => {
+                text.truncate(strip(&text));
+                Cow::Owned(text)
+            }
+        }
+    }
+
+    /**
+     * The number of lines as `str::lines` counts them, leaving out the empty line after a final line break
+     **/
+    fn line_count(&self) -> usize {
+        match self.text.len_chars() {
+            0 => 0,
+            chars if self.text.char(chars - 1) == '\n' => self.text.len_lines() - 1,
+            _ => self.text.len_lines(),
+        }
+    }
+
+    /**
+     * The char index in the text of an editor position, clamped to the end of its line
+     **/
+    fn char_index(&self, position: Position) -> usize {
+        let line = position.line as usize;
+        if line >= self.text.len_lines() {
+            return self.text.len_chars();
+        }
+
+        let (_, offset) = self.offset(position);
+        self.text.line_to_char(line) + self.line(line)[..offset].chars().count()
+    }
+
+    /**
+     * The line and byte offset of an editor position, which counts UTF-16 code units
+     **/
+    pub fn offset(&self, position: Position) -> (usize, usize) {
+        let line = position.line as usize;
+        let text = self.line(line);
+        let mut units = 0;
+
+        for (offset, c) in text.char_indices() {
+            if units >= position.character as usize {
+                return (line, offset);
+            }
+
+            units += c.len_utf16();
+        }
+
+        (line, text.len())
+    }
+
+    pub fn position(&self, line: usize, offset: usize) -> Position {
+        let text = self.line(line);
+        let character = text.get(..offset.min(text.len()))
+            .unwrap_or(&text)
+            .encode_utf16()
+            .count();
+
+        Position::new(line as u32, character as u32)
+    }
+
+    pub fn range(&self, span: Span) -> Range {
+        Range::new(self.position(span.line, span.start), self.position(span.line, span.end))
+    }
+
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        let symbols = self.symbols();
+
+        self.errs.iter()
+            .map(|err| self.diagnostic(err))
+            .chain(symbols.unused().map(|id| self.unused_diagnostic(symbols.symbol(id))))
+            .collect()
+    }
+
+    pub fn diagnostic(&self, err: &ParserErr) -> Diagnostic {
+        Diagnostic {
+            range: self.range(err.span()),
+            severity: Some(DiagnosticSeverity::ERROR),
+            source: Some("props".to_string()),
+            message: err.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn unused_diagnostic(&self, symbol: &Symbol) -> Diagnostic {
+        Diagnostic {
+            range: self.range(symbol.span),
+            severity: Some(DiagnosticSeverity::WARNING),
+            source: Some("props".to_string()),
+            message: format!("Binding {} is never used", symbol.name),
+            tags: Some(vec![DiagnosticTag::UNNECESSARY]),
+            ..Default::default()
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use lsp_types::{Hover, HoverContents, MarkupContent, MarkupKind, Position};
+use props_parser::symbols::{Symbol, SymbolKind};
+use props_parser::types::Type;
+use crate::document::Document;
+
+pub(crate) fn hover(document: &Document, position: Position) -> Option<Hover> {
+    let (line, offset) = document.offset(position);
+    let symbols = document.symbols();
+
+    let (span, value) = match symbols.symbol_at(line, offset) {
+        Some(id) => {
+            let symbol = symbols.symbol(id);
+            let span = symbols.references(id)
+                .chain([symbol.span])
+                .find(|span| span.contains(line, offset))?;
+
+            (span, describe(symbol))
+        }
+        None => {
+            let (span, type_) = symbols.type_at(line, offset)?;
+            (span, format!("```props\n{}\n```", type_))
+        }
+    };
+
+    Some(Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value,
+        }),
+        range: Some(document.range(span)),
+    })
+}
+
+fn describe(symbol: &Symbol) -> String {
+    let declared = symbol.annotation.as_ref().unwrap_or(&symbol.type_);
+    let signature = match (symbol.kind, symbol.mutable) {
+        (SymbolKind::Type, _) => format!("type {} = {}", symbol.name, declared),
+        (SymbolKind::Trait, _) => format!("trait {}", symbol.name),
+        (SymbolKind::Parameter, _) => format!("(parameter) {}: {}", symbol.name, declared),
+        (_, true) => format!("mut {}: {}", symbol.name, declared),
+        _ => format!("{}: {}", symbol.name, declared),
+    };
+    let mut value = format!("```props\n{}\n```", signature);
+
+    // an annotated binding may hold a narrower value, e.g. a U8 in an I32
+    if declared != &symbol.type_ && symbol.type_ != Type::Undefined {
+        value.push_str(&format!("\n\nInferred `{}`", symbol.type_));
+    }
+
+    if let Some(doc) = &symbol.doc {
+        value.push_str("\n\n---\n\n");
+        value.push_str(doc);
+    }
+
+    value
+}
@@ -0,0 +1,113 @@
+use lsp_types::{InlayHint, InlayHintKind, InlayHintLabel, Range};
+use props_parser::lexer::Lexer;
+use props_parser::nodes::Span;
+use props_parser::symbols::SymbolKind;
+use props_parser::tokens::Token;
+use props_parser::types::Type;
+use crate::calls::calls;
+use crate::document::Document;
+
+/**
+ * Hints at the types the typer inferred where no annotation is written, and at the parameter each call argument is passed to
+ **/
+pub(crate) fn inlay_hints(document: &Document, range: Range) -> Vec<InlayHint> {
+    let tokens = Lexer::lex(&document.text());
+    let mut hints = vec![];
+
+    binding_types(document, &tokens, &mut hints);
+    return_types(document, &tokens, &mut hints);
+    for line in tokens.split(|(token, _)| token == &Token::Newline) {
+        parameter_names(document, line, &mut hints);
+    }
+
+    hints.retain(|hint| range.start <= hint.position && hint.position <= range.end);
+    hints.sort_by_key(|hint| (hint.position.line, hint.position.character));
+    hints
+}
+
+/**
+ * The type after each binding and parameter declared without an annotation.
+ * Functions are left out as their parameters and return type are hinted where they are written
+ **/
+fn binding_types(document: &Document, tokens: &[(Token, Span)], hints: &mut Vec<InlayHint>) {
+    for symbol in document.symbols().symbols() {
+        if !matches!(symbol.kind, SymbolKind::Variable | SymbolKind::Parameter) || symbol.body.is_some() || symbol.type_ == Type::Undefined {
+            continue;
+        }
+
+        let annotated = tokens.iter()
+            .skip_while(|(_, span)| *span != symbol.span)
+            .nth(1)
+            .is_some_and(|(token, _)| token == &Token::TypeAnnotator);
+
+        if !annotated {
+            hints.push(hint(document, symbol.span, format!(": {}", symbol.type_), InlayHintKind::TYPE));
+        }
+    }
+}
+
+/**
+ * The return type after the parameter list of each function literal
+ **/
+fn return_types(document: &Document, tokens: &[(Token, Span)], hints: &mut Vec<InlayHint>) {
+    let symbols = document.symbols();
+    let mut pipes = tokens.iter().filter(|(token, _)| token == &Token::Pipe).map(|(_, span)| *span);
+
+    // pipes only delimit parameter lists, so they come in pairs
+    while let (Some(open), Some(close)) = (pipes.next(), pipes.next()) {
+        let Some((span, Type::Function(_, return_type, _))) = symbols.type_at(open.line, open.start) else {
+            continue;
+        };
+
+        if span == open && **return_type != Type::Undefined {
+            hints.push(InlayHint {
+                padding_left: Some(true),
+                ..hint(document, close, format!("-> {}", return_type), InlayHintKind::TYPE)
+            });
+        }
+    }
+}
+
+/**
+ * The name of the parameter before each argument of the calls on a line, unless the argument is a binding of the same name
+ **/
+fn parameter_names(document: &Document, line: &[(Token, Span)], hints: &mut Vec<InlayHint>) {
+    let symbols = document.symbols();
+
+    for call in calls(line) {
+        let Some(params) = symbols.symbol_at(call.callee.line, call.callee.start).and_then(|id| symbols.parameters(id)) else {
+            continue;
+        };
+
+        // a method's receiver is the value it is accessed on
+        let params = params.iter().skip(call.method as usize);
+
+        for (arg, param) in call.args.iter().zip(params) {
+            let same_name = line.iter().any(|(token, span)| span == arg && matches!(token, Token::Ident(name) if name == &param.name));
+
+            if !same_name {
+                hints.push(InlayHint {
+                    position: document.position(arg.line, arg.start),
+                    padding_right: Some(true),
+                    ..hint(document, *arg, format!("{}:", param.name), InlayHintKind::PARAMETER)
+                });
+            }
+        }
+    }
+}
+
+/**
+ * A hint placed after a span
+ **/
+fn hint(document: &Document, span: Span, label: String, kind: InlayHintKind) -> InlayHint {
+    InlayHint {
+        position: document.position(span.line, span.end),
+        label: InlayHintLabel::String(label),
+        kind: Some(kind),
+        text_edits: None,
+        tooltip: None,
+        padding_left: None,
+        padding_right: None,
+        data: None,
+    }
+}
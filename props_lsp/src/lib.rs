@@ -0,0 +1,13 @@
+pub mod document;
+pub mod server;
+mod calls;
+mod code_actions;
+mod completion;
+mod definition;
+mod hover;
+mod inlay_hints;
+mod outline;
+mod rename;
+mod semantic_tokens;
+mod signature_help;
+mod workspace;
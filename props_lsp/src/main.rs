@@ -1,3 +1,17 @@
-fn main() {
-    println!("Hello, world!");
+use std::error::Error;
+use lsp_server::Connection;
+use props_lsp::server::Server;
+
+fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
+    // stdout carries the protocol, anything else has to go to stderr
+    let (connection, io_threads) = Connection::stdio();
+    let capabilities = serde_json::to_value(Server::capabilities())?;
+    let params = serde_json::from_value(connection.initialize(capabilities)?)?;
+
+    let mut server = Server::new();
+    server.initialize(params);
+    server.run(&connection)?;
+    io_threads.join()?;
+
+    Ok(())
 }
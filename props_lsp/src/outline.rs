@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+use lsp_types::{DocumentSymbol, Range, SymbolKind as LspSymbolKind};
+use props_parser::nodes::{AstNode, Expression, Identifier, Span};
+use props_parser::symbols::{Symbol, SymbolKind};
+use crate::document::Document;
+
+/**
+ * The declarations of a document as a tree: bindings, with the bindings of the functions they are bound to,
+ * type aliases, traits with their methods and impls with the methods they implement
+ **/
+pub(crate) fn document_symbols(document: &Document) -> Vec<DocumentSymbol> {
+    let declared: HashMap<Span, &Symbol> = document.symbols()
+        .symbols()
+        .iter()
+        .map(|symbol| (symbol.span, symbol))
+        .collect();
+
+    outline(document, &declared, document.ast())
+}
+
+fn outline(document: &Document, declared: &HashMap<Span, &Symbol>, nodes: &[AstNode]) -> Vec<DocumentSymbol> {
+    let mut items = vec![];
+
+    for node in nodes {
+        match node {
+            AstNode::Assignment(ident, expr) => {
+                let mut spans = vec![];
+                declared_spans(ident, &mut spans);
+
+                for symbol in spans.iter().filter_map(|span| declared.get(span)) {
+                    let item = match expr {
+                        Expression::FuncLiteral { statements, end, .. } if spans.len() == 1 => DocumentSymbol {
+                            children: Some(outline(document, declared, statements)),
+                            ..item(document, symbol, symbol.span, *end)
+                        },
+                        _ => item(document, symbol, symbol.span, symbol.span),
+                    };
+
+                    items.push(item);
+                }
+            }
+            AstNode::TypeAlias(_, _, _, span) => items.extend(declared.get(span).map(|symbol| item(document, symbol, *span, *span))),
+            AstNode::Trait(_, methods, span) => {
+                let Some(symbol) = declared.get(span) else {
+                    continue;
+                };
+
+                let methods: Vec<DocumentSymbol> = methods.iter()
+                    .filter_map(|method| declared.get(&method.span()))
+                    .map(|method| item(document, method, method.span, method.span))
+                    .collect();
+                let end = methods.last().map_or(*span, |method| Span::new(method.range.end.line as usize, 0, 0));
+
+                items.push(DocumentSymbol {
+                    children: Some(methods),
+                    ..item(document, symbol, *span, end)
+                });
+            }
+            AstNode::Impl(name, type_, methods, span) => {
+                let methods = outline(document, declared, methods);
+                let end = methods.last().map_or(*span, |method| Span::new(method.range.end.line as usize, 0, 0));
+
+                #[allow(deprecated)]
+                items.push(DocumentSymbol {
+                    name: format!("impl {} for {}", name, type_),
+                    detail: None,
+                    kind: LspSymbolKind::OBJECT,
+                    tags: None,
+                    deprecated: None,
+                    range: range(document, *span, end),
+                    selection_range: document.range(*span),
+                    children: Some(methods),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    items
+}
+
+/**
+ * The spans of the names an assignment declares, which are several when destructuring
+ **/
+fn declared_spans(ident: &Identifier, spans: &mut Vec<Span>) {
+    match ident {
+        Identifier::Identifier(_, _, span) => spans.push(*span),
+        Identifier::Mutable(ident) => declared_spans(ident, spans),
+        Identifier::Compound(idents) => idents.iter().for_each(|ident| declared_spans(ident, spans)),
+        Identifier::Accessor(..) | Identifier::SafeAccessor(..) => {}
+    }
+}
+
+/**
+ * An item for a symbol whose declaration starts at a span and runs to the end of the line of another
+ **/
+fn item(document: &Document, symbol: &Symbol, start: Span, end: Span) -> DocumentSymbol {
+    let kind = match symbol.kind {
+        SymbolKind::Variable if symbol.body.is_some() => LspSymbolKind::FUNCTION,
+        SymbolKind::Variable | SymbolKind::Parameter => LspSymbolKind::VARIABLE,
+        SymbolKind::Method => LspSymbolKind::METHOD,
+        SymbolKind::Type => LspSymbolKind::STRUCT,
+        SymbolKind::Trait => LspSymbolKind::INTERFACE,
+    };
+    let detail = match symbol.kind {
+        SymbolKind::Trait => None,
+        _ => Some(symbol.annotation.as_ref().unwrap_or(&symbol.type_).to_string()),
+    };
+
+    #[allow(deprecated)]
+    DocumentSymbol {
+        name: symbol.name.clone(),
+        detail,
+        kind,
+        tags: None,
+        deprecated: None,
+        range: range(document, start, end),
+        selection_range: document.range(symbol.span),
+        children: None,
+    }
+}
+
+/**
+ * From the start of a span to the end of the line of another
+ **/
+fn range(document: &Document, start: Span, end: Span) -> Range {
+    Range::new(document.position(start.line, start.start), document.position(end.line, document.line(end.line).len()))
+}
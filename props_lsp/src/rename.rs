@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+use lsp_types::{Position, PrepareRenameResponse, TextEdit, Uri, WorkspaceEdit};
+use props_parser::lexer::Lexer;
+use props_parser::nodes::Span;
+use props_parser::symbols::{SymbolKind, SymbolTable};
+use props_parser::tokens::Token;
+use props_parser::types::PRIM_TYPES;
+use crate::document::Document;
+
+const BUILTIN_TYPES: [&str; 4] = ["Unit", "Option", "Result", "Self"];
+
+pub(crate) fn prepare_rename(document: &Document, position: Position) -> Result<Option<PrepareRenameResponse>, String> {
+    let (line, offset) = document.offset(position);
+
+    Ok(target(document, line, offset)?.map(|(_, name, span)| PrepareRenameResponse::RangeWithPlaceholder {
+        range: document.range(span),
+        placeholder: name,
+    }))
+}
+
+/**
+ * Renames the symbol at a position everywhere it is used, refusing names which would change what another name refers to.
+ * Documents cannot refer to each other's names, so the edits stay within the document
+ **/
+pub(crate) fn rename(document: &Document, uri: &Uri, position: Position, new_name: &str) -> Result<Option<WorkspaceEdit>, String> {
+    let (line, offset) = document.offset(position);
+    let Some((id, name, _)) = target(document, line, offset)? else {
+        return Ok(None);
+    };
+
+    // keywords and anything else which does not lex as a single identifier are refused
+    if !matches!(Lexer::lex(new_name).as_slice(), [(Token::Ident(ident), _), (Token::Newline, _)] if ident == new_name) {
+        return Err(format!("`{}` is not a valid name", new_name));
+    }
+
+    let symbols = document.symbols();
+    let mut sites: Vec<Span> = match symbols.symbol(id).kind {
+        SymbolKind::Variable | SymbolKind::Parameter => {
+            check_shadowing(symbols, id, new_name)?;
+            symbols.references(id).chain([symbols.symbol(id).span]).collect()
+        }
+        SymbolKind::Method => {
+            let methods = implementations(symbols, id);
+            if let Some(other) = symbols.symbols().iter().enumerate().find(|(other, symbol)| symbol.kind == SymbolKind::Method && symbol.name == new_name && !methods.contains(other)) {
+                return Err(format!("A method named `{}` is already declared on line {}", new_name, other.1.span.line + 1));
+            }
+
+            methods.iter()
+                .flat_map(|method| symbols.references(*method).chain([symbols.symbol(*method).span]))
+                .collect()
+        }
+        SymbolKind::Type | SymbolKind::Trait => {
+            if PRIM_TYPES.contains_key(new_name) || BUILTIN_TYPES.contains(&new_name) {
+                return Err(format!("`{}` is a builtin type", new_name));
+            }
+
+            if let Some(other) = symbols.symbols().iter().find(|symbol| matches!(symbol.kind, SymbolKind::Type | SymbolKind::Trait) && symbol.name == new_name) {
+                return Err(format!("A type named `{}` is already declared on line {}", new_name, other.span.line + 1));
+            }
+
+            type_names(document, id, &name)
+        }
+    };
+
+    sites.sort_by_key(|span| (span.line, span.start));
+    sites.dedup();
+
+    let edits = sites.into_iter()
+        .map(|span| TextEdit::new(document.range(span), new_name.to_string()))
+        .collect();
+
+    Ok(Some(WorkspaceEdit::new(HashMap::from([(uri.clone(), edits)]))))
+}
+
+/**
+ * The symbol named by the identifier at a position with the identifier's name and span
+ **/
+fn target(document: &Document, line: usize, offset: usize) -> Result<Option<(usize, String, Span)>, String> {
+    let Some((name, span)) = Lexer::lex(&document.text()).into_iter().find_map(|(token, span)| match token {
+        Token::Ident(name) if span.contains(line, offset) => Some((name, span)),
+        _ => None,
+    }) else {
+        return Ok(None);
+    };
+
+    let symbols = document.symbols();
+    if let Some(id) = symbols.symbol_at(line, offset) {
+        return Ok(Some((id, name, span)));
+    }
+
+    // type names in annotations and impls are not recorded as references
+    let declared = symbols.symbols().iter().position(|symbol| matches!(symbol.kind, SymbolKind::Type | SymbolKind::Trait) && symbol.name == name);
+    match declared {
+        Some(id) => Ok(Some((id, name, span))),
+        None if PRIM_TYPES.contains_key(&name) || BUILTIN_TYPES.contains(&name.as_str()) => Err(format!("`{}` is a builtin type", name)),
+        None => Ok(None),
+    }
+}
+
+/**
+ * Refuses a new name for a binding if it would hide, or be hidden by, another binding where either is used
+ **/
+fn check_shadowing(symbols: &SymbolTable, id: usize, new_name: &str) -> Result<(), String> {
+    let symbol = symbols.symbol(id);
+    let named = |line: usize, pos: usize| symbols.visible_at(line, pos).into_iter().find(|other| other.name == new_name && !std::ptr::eq(*other, symbol));
+
+    if let Some(other) = named(symbol.span.line, symbol.span.start) {
+        return Err(format!("`{}` would shadow `{}` declared on line {}", symbol.name, new_name, other.span.line + 1));
+    }
+
+    for span in symbols.references(id) {
+        if let Some(other) = named(span.line, span.start) {
+            return Err(format!("`{}` declared on line {} would shadow `{}` on line {}", new_name, other.span.line + 1, symbol.name, span.line + 1));
+        }
+    }
+
+    let shadowing = symbols.symbols().iter()
+        .filter(|other| other.name == new_name && matches!(other.kind, SymbolKind::Variable | SymbolKind::Parameter))
+        .find(|other| symbols.visible_at(other.span.line, other.span.start).into_iter().any(|visible| std::ptr::eq(visible, symbol)));
+
+    match shadowing {
+        Some(other) => Err(format!("`{}` would be shadowed by `{}` declared on line {}", symbol.name, new_name, other.span.line + 1)),
+        None => Ok(()),
+    }
+}
+
+/**
+ * A trait's method signature together with each of its implementations, which have to keep the same name
+ **/
+fn implementations(symbols: &SymbolTable, id: usize) -> Vec<usize> {
+    let symbol = symbols.symbol(id);
+    let is_method = |other: &usize| symbols.symbol(*other).kind == SymbolKind::Method && symbols.symbol(*other).name == symbol.name;
+
+    // implementations refer to the signature from where they are declared
+    let signature = (0..symbols.symbols().len())
+        .filter(is_method)
+        .find(|signature| symbols.references(*signature).any(|span| span == symbol.span))
+        .unwrap_or(id);
+
+    let implemented: Vec<Span> = symbols.references(signature).collect();
+    let mut methods: Vec<usize> = (0..symbols.symbols().len())
+        .filter(is_method)
+        .filter(|method| implemented.contains(&symbols.symbol(*method).span))
+        .collect();
+    methods.push(signature);
+    methods
+}
+
+/**
+ * Every place a type or trait is named, which are the identifiers not bound to anything else
+ **/
+fn type_names(document: &Document, id: usize, name: &str) -> Vec<Span> {
+    let symbols = document.symbols();
+
+    Lexer::lex(&document.text())
+        .into_iter()
+        .filter(|(token, span)| matches!(token, Token::Ident(ident) if ident == name) && symbols.symbol_at(span.line, span.start).is_none_or(|other| other == id))
+        .map(|(_, span)| span)
+        .collect()
+}
@@ -0,0 +1,135 @@
+use std::collections::HashMap;
+use lsp_types::{SemanticToken, SemanticTokenModifier, SemanticTokenType, SemanticTokens, SemanticTokensDelta, SemanticTokensEdit, SemanticTokensLegend};
+use props_parser::nodes::Span;
+use props_parser::symbols::SymbolKind;
+use props_parser::tokens::Token;
+use props_parser::types::{Type, PRIM_TYPES};
+use crate::document::Document;
+
+// the order is the index each type is encoded as
+const TOKEN_TYPES: [SemanticTokenType; 11] = [
+    SemanticTokenType::VARIABLE,
+    SemanticTokenType::PARAMETER,
+    SemanticTokenType::FUNCTION,
+    SemanticTokenType::METHOD,
+    SemanticTokenType::PROPERTY,
+    SemanticTokenType::TYPE,
+    SemanticTokenType::KEYWORD,
+    SemanticTokenType::NUMBER,
+    SemanticTokenType::STRING,
+    SemanticTokenType::OPERATOR,
+    SemanticTokenType::COMMENT,
+];
+
+const TOKEN_MODIFIERS: [SemanticTokenModifier; 2] = [
+    SemanticTokenModifier::DECLARATION,
+    SemanticTokenModifier::READONLY,
+];
+
+pub(crate) fn legend() -> SemanticTokensLegend {
+    SemanticTokensLegend {
+        token_types: TOKEN_TYPES.to_vec(),
+        token_modifiers: TOKEN_MODIFIERS.to_vec(),
+    }
+}
+
+/**
+ * Classifies every token of the document, using the typer's symbols to tell identifiers apart
+ **/
+pub(crate) fn semantic_tokens(document: &Document) -> Vec<SemanticToken> {
+    let symbols = document.symbols();
+    let names: HashMap<Span, usize> = symbols.span_index();
+    let mut result = vec![];
+    let (mut prev_line, mut prev_start) = (0, 0);
+    let mut prev_token = None;
+
+    for (token, span) in document.tokens() {
+        let after_access = matches!(prev_token.replace(token), Some(Token::Period | Token::SafeAccess));
+        let mut modifiers = 0;
+        let token_type = match token {
+            Token::Ident(name) => {
+                let symbol = names.get(span).map(|id| symbols.symbol(*id));
+
+                if symbol.is_some_and(|symbol| symbol.span == *span) {
+                    modifiers |= 1;
+                }
+
+                match symbol {
+                    // members read like fields, they are only called through the access
+                    _ if after_access => SemanticTokenType::PROPERTY,
+                    Some(symbol) => {
+                        if !symbol.mutable {
+                            modifiers |= 2;
+                        }
+
+                        match symbol.kind {
+                            SymbolKind::Variable if matches!(symbol.type_, Type::Function(..)) => SemanticTokenType::FUNCTION,
+                            SymbolKind::Variable => SemanticTokenType::VARIABLE,
+                            SymbolKind::Parameter => SemanticTokenType::PARAMETER,
+                            SymbolKind::Method => SemanticTokenType::METHOD,
+                            SymbolKind::Type | SymbolKind::Trait => SemanticTokenType::TYPE,
+                        }
+                    }
+                    // names in annotations are not symbols, builtin types and type parameters are capitalized
+                    None if PRIM_TYPES.contains_key(name) || name.starts_with(char::is_uppercase) => SemanticTokenType::TYPE,
+                    None => continue,
+                }
+            }
+            Token::Return | Token::As | Token::Type | Token::Trait | Token::Impl | Token::For |
+            Token::None | Token::Mut | Token::Impure => SemanticTokenType::KEYWORD,
+            Token::Number(_) => SemanticTokenType::NUMBER,
+            Token::StringLiteral(_) | Token::CharLiteral(_) => SemanticTokenType::STRING,
+            Token::Comment => SemanticTokenType::COMMENT,
+            Token::Assignment | Token::Arrow | Token::Question | Token::Coalesce |
+            Token::Addition | Token::Subtraction | Token::Multiplication | Token::Division | Token::Mod | Token::Power |
+            Token::Not | Token::Equality | Token::GreaterThan | Token::LessThan | Token::GreaterEqual | Token::LessEqual => SemanticTokenType::OPERATOR,
+            _ => continue,
+        };
+
+        let start = document.position(span.line, span.start);
+        let end = document.position(span.line, span.end);
+        let (line, start) = (start.line, start.character);
+        let delta_line = line - prev_line;
+        let delta_start = if delta_line == 0 { start - prev_start } else { start };
+
+        result.push(SemanticToken {
+            delta_line,
+            delta_start,
+            length: end.character - start,
+            token_type: TOKEN_TYPES.iter().position(|t| t == &token_type).unwrap_or_default() as u32,
+            token_modifiers_bitset: modifiers,
+        });
+
+        (prev_line, prev_start) = (line, start);
+    }
+
+    result
+}
+
+/**
+ * The edit turning the previous tokens into the current ones, replacing everything between their common start and end
+ **/
+pub(crate) fn delta(previous: &SemanticTokens, current: SemanticTokens) -> SemanticTokensDelta {
+    let (old, new) = (&previous.data, &current.data);
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..].iter().rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let edits = if prefix == old.len() && prefix == new.len() {
+        vec![]
+    } else {
+        // positions count the integers of the encoded tokens, five per token
+        vec![SemanticTokensEdit {
+            start: prefix as u32 * 5,
+            delete_count: (old.len() - prefix - suffix) as u32 * 5,
+            data: Some(new[prefix..new.len() - suffix].to_vec()),
+        }]
+    };
+
+    SemanticTokensDelta {
+        result_id: current.result_id,
+        edits,
+    }
+}
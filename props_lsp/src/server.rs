@@ -0,0 +1,259 @@
+use std::collections::HashMap;
+use std::error::Error;
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::notification::{DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, Notification as _, PublishDiagnostics};
+use lsp_types::request::{CodeActionRequest, Completion, DocumentSymbolRequest, GotoDefinition, HoverRequest, InlayHintRequest, PrepareRenameRequest, References, Rename, Request as _, SignatureHelpRequest, SemanticTokensFullDeltaRequest, SemanticTokensFullRequest, WorkspaceSymbolRequest};
+use lsp_types::{CodeActionProviderCapability, CompletionOptions, DocumentSymbolResponse, InitializeParams, HoverProviderCapability, OneOf, PublishDiagnosticsParams, RenameOptions, SemanticTokens, SemanticTokensFullDeltaResult, SemanticTokensFullOptions, SemanticTokensOptions, SemanticTokensResult, SemanticTokensServerCapabilities, ServerCapabilities, SignatureHelpOptions, TextDocumentSyncCapability, TextDocumentSyncKind, Uri, WorkspaceSymbolResponse};
+use crate::document::Document;
+use crate::{code_actions, completion, definition, hover, inlay_hints, outline, rename, semantic_tokens, signature_help};
+use crate::workspace::{file_path, WorkspaceIndex};
+
+/**
+ * Answers requests about the open documents, keeping each one parsed and typed as it changes
+ **/
+#[derive(Default)]
+pub struct Server {
+    documents: HashMap<Uri, Document>,
+    // the tokens last sent for each document, which deltas are computed against
+    semantic_tokens: HashMap<Uri, SemanticTokens>,
+    result_id: usize,
+    workspace: WorkspaceIndex,
+}
+
+impl Server {
+    pub fn new() -> Self {
+        Server::default()
+    }
+
+    /**
+     * Takes the folders opened by the client as the workspace whose files symbols are searched in
+     **/
+    pub fn initialize(&mut self, params: InitializeParams) {
+        #[allow(deprecated)]
+        let folders = match params.workspace_folders {
+            Some(folders) => folders.iter().filter_map(|folder| file_path(&folder.uri)).collect(),
+            None => params.root_uri.iter().filter_map(file_path).collect(),
+        };
+
+        self.workspace = WorkspaceIndex::new(folders);
+    }
+
+    pub fn capabilities() -> ServerCapabilities {
+        ServerCapabilities {
+            text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::INCREMENTAL)),
+            hover_provider: Some(HoverProviderCapability::Simple(true)),
+            definition_provider: Some(OneOf::Left(true)),
+            references_provider: Some(OneOf::Left(true)),
+            completion_provider: Some(CompletionOptions {
+                trigger_characters: Some(vec![".".to_string(), ":".to_string()]),
+                ..Default::default()
+            }),
+            // arguments are separated by whitespace rather than commas
+            signature_help_provider: Some(SignatureHelpOptions {
+                trigger_characters: Some(vec![" ".to_string()]),
+                ..Default::default()
+            }),
+            document_symbol_provider: Some(OneOf::Left(true)),
+            workspace_symbol_provider: Some(OneOf::Left(true)),
+            code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
+            inlay_hint_provider: Some(OneOf::Left(true)),
+            rename_provider: Some(OneOf::Right(RenameOptions {
+                prepare_provider: Some(true),
+                work_done_progress_options: Default::default(),
+            })),
+            semantic_tokens_provider: Some(SemanticTokensServerCapabilities::SemanticTokensOptions(SemanticTokensOptions {
+                legend: semantic_tokens::legend(),
+                full: Some(SemanticTokensFullOptions::Delta { delta: Some(true) }),
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+    }
+
+    pub fn run(&mut self, connection: &Connection) -> Result<(), Box<dyn Error + Send + Sync>> {
+        for message in &connection.receiver {
+            match message {
+                Message::Request(request) => {
+                    if connection.handle_shutdown(&request)? {
+                        return Ok(());
+                    }
+
+                    connection.sender.send(Message::Response(self.handle_request(request)))?;
+                }
+                Message::Notification(notification) => {
+                    for notification in self.handle_notification(notification) {
+                        connection.sender.send(Message::Notification(notification))?;
+                    }
+                }
+                Message::Response(_) => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn document(&self, uri: &Uri) -> Option<&Document> {
+        self.documents.get(uri)
+    }
+
+    pub fn handle_request(&mut self, request: Request) -> Response {
+        match request.method.as_str() {
+            HoverRequest::METHOD => self.respond::<HoverRequest>(request, |server, params| {
+                let position = params.text_document_position_params;
+                server.document(&position.text_document.uri)
+                    .and_then(|document| hover::hover(document, position.position))
+            }),
+            GotoDefinition::METHOD => self.respond::<GotoDefinition>(request, |server, params| {
+                let position = params.text_document_position_params;
+                server.document(&position.text_document.uri)
+                    .and_then(|document| definition::definition(document, &position.text_document.uri, position.position))
+            }),
+            References::METHOD => self.respond::<References>(request, |server, params| {
+                let position = params.text_document_position;
+                server.document(&position.text_document.uri)
+                    .and_then(|document| definition::references(document, &position.text_document.uri, position.position, params.context.include_declaration))
+            }),
+            Completion::METHOD => self.respond::<Completion>(request, |server, params| {
+                let position = params.text_document_position;
+                server.document(&position.text_document.uri)
+                    .and_then(|document| completion::completion(document, position.position))
+            }),
+            SignatureHelpRequest::METHOD => self.respond::<SignatureHelpRequest>(request, |server, params| {
+                let position = params.text_document_position_params;
+                server.document(&position.text_document.uri)
+                    .and_then(|document| signature_help::signature_help(document, position.position))
+            }),
+            DocumentSymbolRequest::METHOD => self.respond::<DocumentSymbolRequest>(request, |server, params| {
+                server.document(&params.text_document.uri)
+                    .map(|document| DocumentSymbolResponse::Nested(outline::document_symbols(document)))
+            }),
+            WorkspaceSymbolRequest::METHOD => self.respond::<WorkspaceSymbolRequest>(request, |server, params| {
+                let documents: Vec<(&Uri, &Document)> = server.documents.iter().collect();
+                Some(WorkspaceSymbolResponse::Nested(server.workspace.search(&params.query, &documents)))
+            }),
+            CodeActionRequest::METHOD => self.respond::<CodeActionRequest>(request, |server, params| {
+                server.document(&params.text_document.uri)
+                    .map(|document| code_actions::code_actions(document, &params.text_document.uri, params.range))
+            }),
+            InlayHintRequest::METHOD => self.respond::<InlayHintRequest>(request, |server, params| {
+                server.document(&params.text_document.uri)
+                    .map(|document| inlay_hints::inlay_hints(document, params.range))
+            }),
+            PrepareRenameRequest::METHOD => self.try_respond::<PrepareRenameRequest>(request, |server, params| {
+                match server.document(&params.text_document.uri) {
+                    Some(document) => rename::prepare_rename(document, params.position),
+                    None => Ok(None),
+                }
+            }),
+            Rename::METHOD => self.try_respond::<Rename>(request, |server, params| {
+                let position = params.text_document_position;
+                match server.document(&position.text_document.uri) {
+                    Some(document) => rename::rename(document, &position.text_document.uri, position.position, &params.new_name),
+                    None => Ok(None),
+                }
+            }),
+            SemanticTokensFullRequest::METHOD => self.respond::<SemanticTokensFullRequest>(request, |server, params| {
+                server.semantic_tokens(params.text_document.uri).map(SemanticTokensResult::Tokens)
+            }),
+            SemanticTokensFullDeltaRequest::METHOD => self.respond::<SemanticTokensFullDeltaRequest>(request, |server, params| {
+                let uri = params.text_document.uri;
+                let previous = server.semantic_tokens.get(&uri)
+                    .filter(|tokens| tokens.result_id.as_ref() == Some(&params.previous_result_id))
+                    .cloned();
+                let current = server.semantic_tokens(uri)?;
+
+                // a client asking against tokens which were replaced since gets all of them again
+                Some(match previous {
+                    Some(previous) => SemanticTokensFullDeltaResult::TokensDelta(semantic_tokens::delta(&previous, current)),
+                    None => SemanticTokensFullDeltaResult::Tokens(current),
+                })
+            }),
+            _ => Response::new_err(request.id, ErrorCode::MethodNotFound as i32, format!("Unsupported request {}", request.method)),
+        }
+    }
+
+    /**
+     * Updates the documents, returning the notifications to send back
+     **/
+    pub fn handle_notification(&mut self, notification: Notification) -> Vec<Notification> {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let Some(params) = notification_params::<DidOpenTextDocument>(notification) else {
+                    return vec![];
+                };
+
+                let uri = params.text_document.uri;
+                self.documents.insert(uri.clone(), Document::new(params.text_document.text));
+                vec![self.publish_diagnostics(uri)]
+            }
+            DidChangeTextDocument::METHOD => {
+                let Some(params) = notification_params::<DidChangeTextDocument>(notification) else {
+                    return vec![];
+                };
+
+                let uri = params.text_document.uri;
+                let Some(document) = self.documents.get_mut(&uri) else {
+                    return vec![];
+                };
+
+                document.change(params.content_changes);
+                vec![self.publish_diagnostics(uri)]
+            }
+            DidCloseTextDocument::METHOD => {
+                let Some(params) = notification_params::<DidCloseTextDocument>(notification) else {
+                    return vec![];
+                };
+
+                let uri = params.text_document.uri;
+                self.documents.remove(&uri);
+                self.semantic_tokens.remove(&uri);
+                vec![Notification::new(PublishDiagnostics::METHOD.to_string(), PublishDiagnosticsParams::new(uri, vec![], None))]
+            }
+            _ => vec![],
+        }
+    }
+
+    /**
+     * Classifies the tokens of a document, remembering them under a new result id
+     **/
+    fn semantic_tokens(&mut self, uri: Uri) -> Option<SemanticTokens> {
+        let data = semantic_tokens::semantic_tokens(self.documents.get(&uri)?);
+        self.result_id += 1;
+
+        let tokens = SemanticTokens {
+            result_id: Some(self.result_id.to_string()),
+            data,
+        };
+        self.semantic_tokens.insert(uri, tokens.clone());
+        Some(tokens)
+    }
+
+    fn publish_diagnostics(&self, uri: Uri) -> Notification {
+        let diagnostics = self.documents.get(&uri)
+            .map(Document::diagnostics)
+            .unwrap_or_default();
+
+        Notification::new(PublishDiagnostics::METHOD.to_string(), PublishDiagnosticsParams::new(uri, diagnostics, None))
+    }
+
+    fn respond<R: lsp_types::request::Request>(&mut self, request: Request, handler: impl FnOnce(&mut Self, R::Params) -> R::Result) -> Response {
+        self.try_respond::<R>(request, |server, params| Ok(handler(server, params)))
+    }
+
+    /**
+     * Responds to a request which may be refused, with the reason to show to the user
+     **/
+    fn try_respond<R: lsp_types::request::Request>(&mut self, request: Request, handler: impl FnOnce(&mut Self, R::Params) -> Result<R::Result, String>) -> Response {
+        match serde_json::from_value(request.params) {
+            Ok(params) => match handler(self, params) {
+                Ok(result) => Response::new_ok(request.id, result),
+                Err(message) => Response::new_err(request.id, ErrorCode::RequestFailed as i32, message),
+            },
+            Err(err) => Response::new_err(request.id, ErrorCode::InvalidParams as i32, err.to_string()),
+        }
+    }
+}
+
+fn notification_params<N: lsp_types::notification::Notification>(notification: Notification) -> Option<N::Params> {
+    serde_json::from_value(notification.params).ok()
+}
@@ -0,0 +1,78 @@
+use lsp_types::{Documentation, ParameterInformation, ParameterLabel, Position, SignatureHelp, SignatureInformation};
+use props_parser::lexer::Lexer;
+use props_parser::symbols::{Symbol, SymbolKind};
+use props_parser::types::Type;
+use crate::calls::calls;
+use crate::document::Document;
+
+/**
+ * The signature of the innermost call the cursor is in, with the parameter of the argument being written active.
+ * Only the text before the cursor is read, as the call being typed usually does not parse yet
+ **/
+pub(crate) fn signature_help(document: &Document, position: Position) -> Option<SignatureHelp> {
+    let (line, offset) = document.offset(position);
+    let text = &document.line(line)[..offset];
+
+    let call = calls(&Lexer::lex(text))
+        .into_iter()
+        .filter(|call| call.open.is_some())
+        .max_by_key(|call| call.callee.start)?;
+
+    let symbols = document.symbols();
+    let name = &text[call.callee.start..call.callee.end];
+    let id = symbols.symbol_at(line, call.callee.start).or_else(|| callee(document, line, call.callee.start, name, call.method))?;
+
+    let symbol = symbols.symbol(id);
+    let Type::Function(types, return_type, _) = symbol.annotation.as_ref().unwrap_or(&symbol.type_).unalias() else {
+        return None;
+    };
+
+    // a method's receiver is the value it is accessed on
+    let skipped = call.method as usize;
+    let names: Vec<&Symbol> = symbols.parameters(id)
+        .filter(|params| params.len() == types.len())
+        .unwrap_or_default();
+
+    let mut label = symbol.name.clone();
+    let mut parameters = vec![];
+    for (i, type_) in types.iter().enumerate().skip(skipped) {
+        let parameter = match names.get(i) {
+            Some(param) => format!("{}: {}", param.name, type_),
+            None => type_.to_string(),
+        };
+
+        label.push(' ');
+        let start = label.encode_utf16().count() as u32;
+        label.push_str(&parameter);
+        parameters.push(ParameterInformation {
+            label: ParameterLabel::LabelOffsets([start, label.encode_utf16().count() as u32]),
+            documentation: None,
+        });
+    }
+    label.push_str(&format!(" -> {}", return_type));
+
+    Some(SignatureHelp {
+        signatures: vec![SignatureInformation {
+            label,
+            documentation: symbol.doc.clone().map(Documentation::String),
+            parameters: Some(parameters),
+            active_parameter: None,
+        }],
+        active_signature: Some(0),
+        active_parameter: call.open.map(|arg| arg as u32),
+    })
+}
+
+/**
+ * The symbol a name refers to where it is written, as an unfinished statement is not typed and records no references
+ **/
+fn callee(document: &Document, line: usize, pos: usize, name: &str, method: bool) -> Option<usize> {
+    let symbols = document.symbols();
+
+    if method {
+        return symbols.symbols().iter().rposition(|symbol| symbol.kind == SymbolKind::Method && symbol.name == name);
+    }
+
+    let visible = symbols.visible_at(line, pos).into_iter().find(|symbol| symbol.name == name)?;
+    symbols.symbols().iter().position(|symbol| std::ptr::eq(symbol, visible))
+}
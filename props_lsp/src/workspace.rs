@@ -0,0 +1,175 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::SystemTime;
+use lsp_types::{DocumentSymbol, Location, OneOf, Uri, WorkspaceSymbol};
+use crate::document::Document;
+use crate::outline::document_symbols;
+
+/**
+ * The symbols of every .prop file in the workspace folders, each file reparsed only once it changes on disk.
+ * The folders are only walked again once a folder in them changes
+ **/
+#[derive(Default)]
+pub(crate) struct WorkspaceIndex {
+    folders: Vec<PathBuf>,
+    dirs: HashMap<PathBuf, SystemTime>,
+    paths: HashSet<PathBuf>,
+    files: HashMap<PathBuf, (SystemTime, Vec<WorkspaceSymbol>)>,
+}
+
+impl WorkspaceIndex {
+    pub fn new(folders: Vec<PathBuf>) -> Self {
+        WorkspaceIndex {
+            folders,
+            ..Default::default()
+        }
+    }
+
+    /**
+     * Walks the folders again if a file was added to or removed from any folder since the last walk
+     **/
+    fn refresh(&mut self) {
+        let changed = self.dirs.is_empty() || self.dirs.iter().any(|(dir, walked)| modified(dir) != Some(*walked));
+        if !changed {
+            return;
+        }
+
+        self.dirs.clear();
+        self.paths.clear();
+        for folder in self.folders.iter() {
+            prop_files(folder, &mut self.dirs, &mut self.paths);
+        }
+
+        // files which were deleted are not searched anymore
+        self.files.retain(|path, _| self.paths.contains(path));
+    }
+
+    /**
+     * The symbols whose name contains the characters of the query in order, open documents taking the place of the files they were read from
+     **/
+    pub fn search(&mut self, query: &str, documents: &[(&Uri, &Document)]) -> Vec<WorkspaceSymbol> {
+        self.refresh();
+
+        let mut symbols = vec![];
+        for (uri, document) in documents {
+            symbols.extend(flatten(uri, document_symbols(document), None));
+        }
+
+        // open documents are already parsed
+        let open: HashSet<PathBuf> = documents.iter().filter_map(|(uri, _)| file_path(uri)).collect();
+        for path in self.paths.iter() {
+            let Some(uri) = file_uri(path).filter(|_| !open.contains(path)) else {
+                continue;
+            };
+
+            let Some(modified) = modified(path) else {
+                continue;
+            };
+
+            let indexed = self.files.get(path).is_some_and(|(indexed, _)| *indexed == modified);
+            if !indexed {
+                let Ok(text) = fs::read_to_string(path) else {
+                    continue;
+                };
+
+                let document = Document::new(text);
+                self.files.insert(path.clone(), (modified, flatten(&uri, document_symbols(&document), None)));
+            }
+
+            symbols.extend(self.files[path].1.iter().cloned());
+        }
+
+        symbols.retain(|symbol| matches(&symbol.name, query));
+        symbols
+    }
+}
+
+/**
+ * Lists the items of an outline with the item each is nested in
+ **/
+fn flatten(uri: &Uri, items: Vec<DocumentSymbol>, container: Option<&str>) -> Vec<WorkspaceSymbol> {
+    let mut symbols = vec![];
+
+    for item in items {
+        symbols.push(WorkspaceSymbol {
+            name: item.name.clone(),
+            kind: item.kind,
+            tags: None,
+            container_name: container.map(str::to_string),
+            location: OneOf::Left(Location::new(uri.clone(), item.selection_range)),
+            data: None,
+        });
+
+        symbols.extend(flatten(uri, item.children.unwrap_or_default(), Some(&item.name)));
+    }
+
+    symbols
+}
+
+/**
+ * Whether the characters of the query appear in the name in order, ignoring case
+ **/
+fn matches(name: &str, query: &str) -> bool {
+    let mut name = name.chars().flat_map(char::to_lowercase);
+    query.chars()
+        .flat_map(char::to_lowercase)
+        .all(|c| name.any(|n| n == c))
+}
+
+/**
+ * Collects the .prop files in a folder and the folders in it, leaving out hidden folders and build output.
+ * Linked folders are not followed, they may link back to a folder containing them
+ **/
+fn prop_files(folder: &Path, dirs: &mut HashMap<PathBuf, SystemTime>, paths: &mut HashSet<PathBuf>) {
+    let Ok(entries) = fs::read_dir(folder) else {
+        return;
+    };
+
+    if let Some(modified) = modified(folder) {
+        dirs.insert(folder.to_path_buf(), modified);
+    }
+
+    for entry in entries.flatten() {
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+
+        let path = entry.path();
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+
+        if file_type.is_dir() {
+            if !name.starts_with('.') && name != "target" {
+                prop_files(&path, dirs, paths);
+            }
+        } else if path.is_file() && path.extension().is_some_and(|extension| extension == "prop") {
+            paths.insert(path);
+        }
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+pub(crate) fn file_path(uri: &Uri) -> Option<PathBuf> {
+    if !uri.as_str().starts_with("file://") {
+        return None;
+    }
+
+    Some(PathBuf::from(uri.path().as_estr().decode().into_string_lossy().into_owned()))
+}
+
+fn file_uri(path: &Path) -> Option<Uri> {
+    let encoded: String = path.to_str()?
+        .bytes()
+        .map(|b| match b {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'/' | b'-' | b'.' | b'_' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect();
+
+    Uri::from_str(&format!("file://{}", encoded)).ok()
+}
@@ -0,0 +1,96 @@
+mod common;
+
+use lsp_types::request::CodeActionRequest;
+use lsp_types::{CodeActionContext, CodeActionOrCommand, CodeActionParams, Position, Range, TextDocumentIdentifier};
+use props_parser::pipeline::PropsPipeline;
+use common::{apply, open, request, uri};
+
+/** The code actions offered for the lines of a source, as titles and the text each would leave **/
+fn actions(text: &str, lines: std::ops::Range<u32>) -> Vec<(String, String)> {
+    let params = CodeActionParams {
+        text_document: TextDocumentIdentifier::new(uri()),
+        range: Range::new(Position::new(lines.start, 0), Position::new(lines.end, 0)),
+        context: CodeActionContext::default(),
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+    };
+    let actions = request::<CodeActionRequest>(&mut open(text), params).unwrap().unwrap_or_default();
+
+    actions.into_iter()
+        .map(|action| match action {
+            CodeActionOrCommand::CodeAction(action) => {
+                let edits = action.edit.unwrap().changes.unwrap().remove(&uri()).unwrap();
+                (action.title, apply(text, edits))
+            }
+            CodeActionOrCommand::Command(command) => panic!("unexpected command {:?}", command),
+        })
+        .collect()
+}
+
+fn action<'a>(actions: &'a [(String, String)], title: &str) -> Option<&'a str> {
+    actions.iter().find(|(t, _)| t == title).map(|(_, text)| text.as_str())
+}
+
+fn errs(text: &str) -> Vec<String> {
+    PropsPipeline::default().parse(text.to_string()).1.iter().map(|err| err.to_string()).collect()
+}
+
+#[test]
+fn add_type_annotation() {
+    let actions = actions("x = 1, \"s\"\n", 0..1);
+
+    assert_eq!(action(&actions, "Add type annotation `(I16, Str)`"), Some("x: (I16, Str) = 1, \"s\"\n"));
+}
+
+#[test]
+fn no_annotation_for_partly_unknown_types() {
+    for text in ["x = none\n", "y = 1, none\n", "f = |a| a\n"] {
+        let actions = actions(text, 0..1);
+        assert!(actions.iter().all(|(title, _)| !title.starts_with("Add type annotation")), "{}: {:?}", text, actions);
+    }
+}
+
+#[test]
+fn change_annotation() {
+    let text = "x: Str = 1, 2\n";
+    let fixed = actions(text, 0..1);
+
+    assert_eq!(action(&fixed, "Change annotation to `(I16, I16)`"), Some("x: (I16, I16) = 1, 2\n"));
+    assert!(errs(action(&fixed, "Change annotation to `(I16, I16)`").unwrap()).is_empty());
+
+    let actions = actions("x: I32 = none, 1\n", 0..1);
+    assert!(actions.iter().all(|(title, _)| !title.starts_with("Change annotation")), "{:?}", actions);
+}
+
+#[test]
+fn cast_value() {
+    let text = "a: I64 = 1\nb: I32 = a\n";
+    let actions = actions(text, 1..2);
+
+    assert_eq!(action(&actions, "Cast to `I32`"), Some("a: I64 = 1\nb: I32 = a as I32\n"));
+}
+
+#[test]
+fn create_binding_type_checks() {
+    for (text, fixed) in [
+        ("w = z + 1\n", "z = 0\nw = z + 1\n"),
+        ("y: U8 = z\n", "z: U8 = 0\ny: U8 = z\n"),
+        ("y: (I32, Str?) = z\n", "z: (I32, Str?) = (0, none)\ny: (I32, Str?) = z\n"),
+        ("f = |s: Str| s\ny = f z\n", "f = |s: Str| s\nz: Str = \"\"\ny = f z\n"),
+        ("y = z\n", "z = none\ny = z\n"),
+    ] {
+        let line = text.lines().count() as u32 - 1;
+        let actions = actions(text, line..line + 1);
+
+        assert_eq!(action(&actions, "Create binding `z`"), Some(fixed), "{}", text);
+        assert!(errs(fixed).is_empty(), "{}: {:?}", fixed, errs(fixed));
+    }
+}
+
+#[test]
+fn remove_unused_binding() {
+    let text = "f = |a: I32| {\n\tb = 2\n\treturn a\n}\n";
+    let actions = actions(text, 1..2);
+
+    assert_eq!(action(&actions, "Remove unused binding `b`"), Some("f = |a: I32| {\n\treturn a\n}\n"));
+}
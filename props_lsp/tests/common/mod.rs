@@ -0,0 +1,73 @@
+// each test file uses only some of the helpers
+#![allow(dead_code)]
+
+use lsp_server::{Notification, Request, RequestId};
+use lsp_types::notification::{DidOpenTextDocument, Notification as _};
+use lsp_types::request::Request as LspRequest;
+use lsp_types::{
+    DidOpenTextDocumentParams, Position, Range, TextDocumentIdentifier, TextDocumentItem, TextDocumentPositionParams,
+    TextEdit, Uri,
+};
+use props_lsp::server::Server;
+
+pub fn uri() -> Uri {
+    "file:///t.prop".parse().unwrap()
+}
+
+/** A server with a source opened at `uri()` **/
+pub fn open(text: &str) -> Server {
+    let mut server = Server::new();
+    open_at(&mut server, uri(), text);
+    server
+}
+
+pub fn open_at(server: &mut Server, uri: Uri, text: &str) {
+    server.handle_notification(Notification::new(DidOpenTextDocument::METHOD.to_string(), DidOpenTextDocumentParams {
+        text_document: TextDocumentItem::new(uri, "props".to_string(), 1, text.to_string()),
+    }));
+}
+
+/** Sends a request, returning its result or the message of the error it was refused with **/
+pub fn request<R: LspRequest>(server: &mut Server, params: R::Params) -> Result<R::Result, String> {
+    let response = server.handle_request(Request::new(RequestId::from(1), R::METHOD.to_string(), params));
+    if let Some(err) = response.error {
+        return Err(err.message);
+    }
+
+    Ok(serde_json::from_value(response.result.unwrap()).unwrap())
+}
+
+pub fn position(line: u32, character: u32) -> TextDocumentPositionParams {
+    TextDocumentPositionParams::new(TextDocumentIdentifier::new(uri()), Position::new(line, character))
+}
+
+/** The position at the end of a source, where an editor's cursor is while typing it **/
+pub fn end(text: &str) -> TextDocumentPositionParams {
+    let last = text.rsplit('\n').next().unwrap();
+    position(text.matches('\n').count() as u32, last.len() as u32)
+}
+
+pub fn range(line: u32, start: u32, end: u32) -> Range {
+    Range::new(Position::new(line, start), Position::new(line, end))
+}
+
+/** Applies edits to ascii text, each within a line or replacing whole lines, which don't overlap **/
+pub fn apply(text: &str, mut edits: Vec<TextEdit>) -> String {
+    let mut lines: Vec<String> = text.split('\n').map(str::to_string).collect();
+    edits.sort_by_key(|edit| std::cmp::Reverse((edit.range.start.line, edit.range.start.character)));
+
+    for edit in edits {
+        let (start, end) = (edit.range.start, edit.range.end);
+        if start.line == end.line {
+            lines[start.line as usize].replace_range(start.character as usize..end.character as usize, &edit.new_text);
+        } else {
+            // whole lines, from the start of one to the start of another
+            let mut replaced: Vec<String> = edit.new_text.split('\n').map(str::to_string).collect();
+            let last = replaced.pop().unwrap() + &lines[end.line as usize];
+            replaced.push(last);
+            lines.splice(start.line as usize..=end.line as usize, replaced);
+        }
+    }
+
+    lines.join("\n")
+}
@@ -0,0 +1,62 @@
+mod common;
+
+use lsp_types::request::Completion;
+use lsp_types::{CompletionItem, CompletionParams, CompletionResponse};
+use common::{end, open, request};
+
+/** The completions at the end of a source, in the order the editor ranks them **/
+fn complete(text: &str) -> Vec<CompletionItem> {
+    let params = CompletionParams {
+        text_document_position: end(text),
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+        context: None,
+    };
+
+    let mut items = match request::<Completion>(&mut open(text), params).unwrap() {
+        Some(CompletionResponse::Array(items)) => items,
+        response => panic!("unexpected response {:?}", response),
+    };
+    items.sort_by(|a, b| a.sort_text.cmp(&b.sort_text));
+    items
+}
+
+fn labels(items: &[CompletionItem]) -> Vec<&str> {
+    items.iter().map(|item| item.label.as_str()).collect()
+}
+
+#[test]
+fn bindings_fitting_expected_type_first() {
+    let items = complete("a: I32 = 1\ns = \"s\"\nb: I64 = ");
+    let labels = labels(&items);
+
+    assert_eq!(labels[..2], ["a", "s"]);
+    assert!(labels[2..].contains(&"return") && labels[2..].contains(&"none"), "{:?}", labels);
+    assert_eq!(items[0].detail.as_deref(), Some("I32"));
+}
+
+#[test]
+fn bindings_in_scope() {
+    let items = complete("x = 1\nf = |a: I32| {\n\tb = a\n\treturn ");
+    let bindings: Vec<&str> = labels(&items).into_iter().filter(|label| label.len() == 1).collect();
+
+    assert_eq!(bindings, ["a", "b", "f", "x"]);
+}
+
+#[test]
+fn types_after_annotation() {
+    let items = complete("type Point = (I32, I32)\np: ");
+    let labels = labels(&items);
+
+    assert_eq!(labels[0], "Point");
+    assert!(["I32", "Str", "Option", "Result", "Unit"].iter().all(|name| labels.contains(name)), "{:?}", labels);
+}
+
+#[test]
+fn methods_after_accessor() {
+    let source = "trait Show {\n\tshow: (Self) -> I32\n}\nimpl Show for I32 {\n\tshow = |x| x\n}\nn: I32 = 1\ns = \"s\"\n";
+
+    assert_eq!(labels(&complete(&format!("{}m = n.", source))), ["show"]);
+    assert_eq!(labels(&complete(&format!("{}m = n.sh", source))), ["show"]);
+    assert!(complete(&format!("{}m = s.", source)).is_empty());
+}
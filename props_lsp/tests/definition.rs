@@ -0,0 +1,69 @@
+mod common;
+
+use lsp_types::request::{GotoDefinition, References};
+use lsp_types::{GotoDefinitionParams, GotoDefinitionResponse, Range, ReferenceContext, ReferenceParams};
+use common::{open, position, range, request};
+
+const TEXT: &str = "trait Show {\n\tshow: (Self) -> I32\n}\nimpl Show for I32 {\n\tshow = |x| x\n}\nadd = |a: I32 b: I32| {\n\tc = a + b\n\treturn c\n}\nn = add 1 2\nm = n.show\nadd n m\nx = 1\nf = |x: I32| x\n";
+
+fn definition(line: u32, character: u32) -> Option<Range> {
+    let params = GotoDefinitionParams {
+        text_document_position_params: position(line, character),
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+    };
+
+    match request::<GotoDefinition>(&mut open(TEXT), params).unwrap()? {
+        GotoDefinitionResponse::Scalar(location) => Some(location.range),
+        definition => panic!("unexpected definition {:?}", definition),
+    }
+}
+
+fn references(line: u32, character: u32, include_declaration: bool) -> Vec<Range> {
+    let params = ReferenceParams {
+        text_document_position: position(line, character),
+        context: ReferenceContext { include_declaration },
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+    };
+    let locations = request::<References>(&mut open(TEXT), params).unwrap();
+
+    locations.unwrap_or_default().into_iter().map(|location| location.range).collect()
+}
+
+#[test]
+fn definition_of_uses() {
+    // a call, an impure call and a parameter
+    assert_eq!(definition(10, 4), Some(range(6, 0, 3)));
+    assert_eq!(definition(12, 0), Some(range(6, 0, 3)));
+    assert_eq!(definition(7, 5), Some(range(6, 7, 8)));
+}
+
+#[test]
+fn definition_of_method() {
+    assert_eq!(definition(11, 6), Some(range(4, 1, 5)));
+}
+
+#[test]
+fn definition_of_declaration_is_itself() {
+    assert_eq!(definition(13, 0), Some(range(13, 0, 1)));
+}
+
+#[test]
+fn references_of_binding() {
+    assert_eq!(references(6, 0, true), vec![range(6, 0, 3), range(10, 4, 7), range(12, 0, 3)]);
+    assert_eq!(references(10, 4, false), vec![range(10, 4, 7), range(12, 0, 3)]);
+}
+
+#[test]
+fn references_stop_at_shadowing() {
+    // the parameter `x` shadows the binding `x`
+    assert_eq!(references(13, 0, true), vec![range(13, 0, 1)]);
+    assert_eq!(references(14, 5, true), vec![range(14, 5, 6), range(14, 13, 14)]);
+}
+
+#[test]
+fn nothing_on_keywords() {
+    assert_eq!(definition(8, 2), None);
+    assert!(references(8, 2, true).is_empty());
+}
@@ -0,0 +1,58 @@
+mod common;
+
+use lsp_types::request::HoverRequest;
+use lsp_types::{HoverContents, HoverParams, Range};
+use common::{open, position, range, request};
+
+/** The markdown shown hovering a position of a source, and the range it applies to **/
+fn hover(text: &str, line: u32, character: u32) -> Option<(String, Range)> {
+    let params = HoverParams {
+        text_document_position_params: position(line, character),
+        work_done_progress_params: Default::default(),
+    };
+    let hover = request::<HoverRequest>(&mut open(text), params).unwrap();
+
+    hover.map(|hover| match hover.contents {
+        HoverContents::Markup(markup) => (markup.value, hover.range.unwrap()),
+        contents => panic!("unexpected contents {:?}", contents),
+    })
+}
+
+#[test]
+fn binding_with_doc() {
+    let text = "/// The answer\nx = 42\ny: I64 = x\n";
+
+    assert_eq!(hover(text, 1, 0), Some(("```props\nx: I16\n```\n\n---\n\nThe answer".to_string(), range(1, 0, 1))));
+    // a use shows the binding it refers to
+    assert_eq!(hover(text, 2, 9).map(|(_, range)| range), Some(range(2, 9, 10)));
+}
+
+#[test]
+fn annotation_with_inferred_value() {
+    let text = "n: U8 = 1\nmut x: I32 = n\n";
+
+    assert_eq!(hover(text, 1, 4).unwrap().0, "```props\nmut x: I32\n```\n\nInferred `U8`");
+}
+
+#[test]
+fn func_literal_parameter() {
+    // the parameter takes its type from the annotated signature
+    let text = "f: (I32, Str) -> I32 = |a b| a\n";
+
+    assert_eq!(hover(text, 0, 24).unwrap().0, "```props\n(parameter) a: I32\n```");
+    assert_eq!(hover(text, 0, 26).unwrap().0, "```props\n(parameter) b: Str\n```");
+    assert_eq!(hover(text, 0, 29).unwrap().0, "```props\n(parameter) a: I32\n```");
+}
+
+#[test]
+fn expression_type() {
+    let text = "x: I64 = 1\ny = (x + 2) * 3\n";
+
+    assert_eq!(hover(text, 1, 9), Some(("```props\nI16\n```".to_string(), range(1, 9, 10))));
+    assert_eq!(hover(text, 1, 12).unwrap().0, "```props\nI64\n```");
+}
+
+#[test]
+fn nothing_on_whitespace() {
+    assert_eq!(hover("x = 1\n\n", 1, 0), None);
+}
@@ -0,0 +1,110 @@
+use lsp_types::{Position, Range, TextDocumentContentChangeEvent};
+use props_lsp::document::Document;
+use props_parser::lexer::Lexer;
+use props_parser::pipeline::PropsPipeline;
+
+const SOURCES: [&str; 4] = [
+    include_str!("../../examples/example.prop"),
+    "/// Adds two numbers\nadd = |a: I32 b: I32| {\n\tc = a + b\n\treturn c\n}\n\nx = add 1 2\ny: I64 = x\n",
+    "type Point = (I32, I32)\ntrait Show {\n\tshow: (Self) -> I32\n}\nimpl Show for I32 {\n\tshow = |x| x\n}\nn: I32 = 5\nm = n.show\n",
+    "f = |a| {\n\tb = a +\n\treturn (g a\n}\nmut z = none\nz = 'c'\ns = \"unterminated\n",
+];
+
+const SNIPPETS: [&str; 30] = [
+    "", "x", " ", "\n", "\r\n", "\t", " = ", "1", "f a b", "|a| ", "|a: I32 b: I32| ", "{", "}", "{\n", "\n}", "(", ")",
+    ", ", "\"", "'", "// note", "/// doc\n", "return ", "mut ", "type T = I32\n", "impl Show for I32 {\n\tshow = |x| 1\n}\n",
+    "?.", " ?? ", " as I64", "é😀",
+];
+
+/** A xorshift generator, so failures reproduce **/
+struct Random(u64);
+
+impl Random {
+    fn below(&mut self, n: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % n as u64) as usize
+    }
+}
+
+/** The editor position of a char index, counting UTF-16 code units within the line **/
+fn position(text: &str, index: usize) -> Position {
+    let before: Vec<char> = text.chars().take(index).collect();
+    let line = before.iter().filter(|c| **c == '\n').count();
+    let character = before.iter().rev().take_while(|c| **c != '\n').map(|c| c.len_utf16()).sum::<usize>();
+
+    Position::new(line as u32, character as u32)
+}
+
+/** Moves a char index out of the middle of a `\r\n` line break **/
+fn boundary(text: &str, index: usize) -> usize {
+    let chars: Vec<char> = text.chars().collect();
+
+    match (index.checked_sub(1).map(|i| chars[i]), chars.get(index)) {
+        (Some('\r'), Some('\n')) => index - 1,
+        _ => index,
+    }
+}
+
+/** Replaces a random range of the text, returning the change an editor would send for it **/
+fn edit(text: &mut String, random: &mut Random) -> TextDocumentContentChangeEvent {
+    let len = text.chars().count();
+    let start = boundary(text, random.below(len + 1));
+    let end = match random.below(4) {
+        0 | 1 => start,
+        2 => random.below(len + 1).max(start),
+        _ => (start + random.below(4)).min(len),
+    };
+    let end = boundary(text, end).max(start);
+    let snippet = SNIPPETS[random.below(SNIPPETS.len())];
+
+    let change = TextDocumentContentChangeEvent {
+        range: Some(Range::new(position(text, start), position(text, end))),
+        range_length: None,
+        text: snippet.to_string(),
+    };
+
+    let byte = |index: usize| text.char_indices().nth(index).map_or(text.len(), |(byte, _)| byte);
+    text.replace_range(byte(start)..byte(end), snippet);
+    change
+}
+
+fn assert_parsed(document: &Document, text: &str) {
+    let mut pipeline = PropsPipeline::default();
+    let (ast, errs) = pipeline.parse(text.to_string());
+
+    assert_eq!(document.text(), text);
+    assert_eq!(document.tokens().cloned().collect::<Vec<_>>(), Lexer::lex_with_comments(text), "source:\n{}", text);
+    assert_eq!(document.ast(), ast, "source:\n{}", text);
+    assert_eq!(format!("{:?}", document.errs()), format!("{:?}", errs), "source:\n{}", text);
+    assert_eq!(format!("{:?}", document.symbols()), format!("{:?}", pipeline.symbols()), "source:\n{}", text);
+}
+
+#[test]
+fn edits_match_full_parse() {
+    for (seed, source) in SOURCES.iter().enumerate() {
+        let mut random = Random(seed as u64 * 7919 + 1);
+        let mut text = source.to_string();
+        let mut document = Document::new(text.clone());
+
+        for _ in 0..300 {
+            let changes = (0..1 + random.below(3)).map(|_| edit(&mut text, &mut random)).collect();
+            document.change(changes);
+            assert_parsed(&document, &text);
+        }
+    }
+}
+
+#[test]
+fn whole_text_change() {
+    let mut document = Document::new(SOURCES[1].to_string());
+    let mut text = SOURCES[2].to_string();
+    let mut random = Random(3);
+
+    let mut changes = vec![TextDocumentContentChangeEvent { range: None, range_length: None, text: text.clone() }];
+    changes.push(edit(&mut text, &mut random));
+    document.change(changes);
+
+    assert_parsed(&document, &text);
+}
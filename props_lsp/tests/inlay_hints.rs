@@ -0,0 +1,62 @@
+mod common;
+
+use lsp_types::request::InlayHintRequest;
+use lsp_types::{InlayHintKind, InlayHintLabel, InlayHintParams, Position, Range, TextDocumentIdentifier};
+use common::{open, request, uri};
+
+const TEXT: &str = "add = |a: I32 b: I32| a + b\nx = add 1 2\ny: I64 = x\na: I32 = 3\nz = add a x\n";
+
+/** The hints within a range of lines, as their position, label and whether they name a parameter **/
+fn hints(text: &str, lines: std::ops::Range<u32>) -> Vec<(u32, u32, String, bool)> {
+    let params = InlayHintParams {
+        text_document: TextDocumentIdentifier::new(uri()),
+        range: Range::new(Position::new(lines.start, 0), Position::new(lines.end, 0)),
+        work_done_progress_params: Default::default(),
+    };
+    let hints = request::<InlayHintRequest>(&mut open(text), params).unwrap();
+
+    hints.unwrap_or_default()
+        .into_iter()
+        .map(|hint| {
+            let InlayHintLabel::String(label) = hint.label else {
+                panic!("unexpected label {:?}", hint.label);
+            };
+            (hint.position.line, hint.position.character, label, hint.kind == Some(InlayHintKind::PARAMETER))
+        })
+        .collect()
+}
+
+fn hint(line: u32, character: u32, label: &str, parameter: bool) -> (u32, u32, String, bool) {
+    (line, character, label.to_string(), parameter)
+}
+
+#[test]
+fn types_and_parameter_names() {
+    assert_eq!(hints(TEXT, 0..5), vec![
+        hint(0, 21, "-> I32", false),
+        hint(1, 1, ": I32", false),
+        hint(1, 8, "a:", true),
+        hint(1, 10, "b:", true),
+        // an argument named like its parameter needs no hint
+        hint(4, 1, ": I32", false),
+        hint(4, 10, "b:", true),
+    ]);
+}
+
+#[test]
+fn only_hints_in_range() {
+    assert_eq!(hints(TEXT, 1..2), vec![hint(1, 1, ": I32", false), hint(1, 8, "a:", true), hint(1, 10, "b:", true)]);
+}
+
+#[test]
+fn inferred_parameter_types() {
+    // parameters without an annotation take the types of the signature
+    let text = "f: (I32) -> Str = |n| \"s\"\n";
+
+    assert_eq!(hints(text, 0..1), vec![hint(0, 20, ": I32", false), hint(0, 21, "-> Str", false)]);
+}
+
+#[test]
+fn no_hints_for_unknown_types() {
+    assert!(hints("x = y\n", 0..1).is_empty());
+}
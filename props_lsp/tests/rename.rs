@@ -0,0 +1,83 @@
+mod common;
+
+use lsp_types::request::{PrepareRenameRequest, Rename};
+use lsp_types::{PrepareRenameResponse, Range, RenameParams};
+use common::{apply, open, position, range, request, uri};
+
+/** The text after renaming the name at a position, or the reason the rename was refused **/
+fn rename(text: &str, line: u32, character: u32, new_name: &str) -> Result<String, String> {
+    let params = RenameParams {
+        text_document_position: position(line, character),
+        new_name: new_name.to_string(),
+        work_done_progress_params: Default::default(),
+    };
+    let edit = request::<Rename>(&mut open(text), params)?;
+
+    let edits = edit.unwrap().changes.unwrap().remove(&uri()).unwrap();
+    Ok(apply(text, edits))
+}
+
+fn prepare(text: &str, line: u32, character: u32) -> Result<Option<(Range, String)>, String> {
+    match request::<PrepareRenameRequest>(&mut open(text), position(line, character))? {
+        Some(PrepareRenameResponse::RangeWithPlaceholder { range, placeholder }) => Ok(Some((range, placeholder))),
+        None => Ok(None),
+        response => panic!("unexpected response {:?}", response),
+    }
+}
+
+#[test]
+fn renames_declaration_and_uses() {
+    let text = "x = 1\nf = |a: I32| a + x\ny = f x\n";
+
+    assert_eq!(rename(text, 0, 0, "count"), Ok("count = 1\nf = |a: I32| a + count\ny = f count\n".to_string()));
+    assert_eq!(rename(text, 1, 13, "n"), Ok("x = 1\nf = |n: I32| n + x\ny = f x\n".to_string()));
+}
+
+#[test]
+fn refuses_invalid_names() {
+    let text = "x = 1\n";
+
+    assert_eq!(rename(text, 0, 0, "return"), Err("`return` is not a valid name".to_string()));
+    assert_eq!(rename(text, 0, 0, "a b"), Err("`a b` is not a valid name".to_string()));
+}
+
+#[test]
+fn refuses_shadowing() {
+    // the parameter would hide the binding it uses
+    let text = "a = 1\nf = |b: I32| b + a\n";
+    assert_eq!(rename(text, 1, 5, "a"), Err("`b` would shadow `a` declared on line 1".to_string()));
+
+    // the binding would be hidden by the parameter where it is used
+    assert_eq!(rename(text, 0, 0, "b"), Err("`b` declared on line 2 would shadow `a` on line 2".to_string()));
+
+    // names which are never visible together can be reused
+    let text = "f = |a: I32| a\ng = |b: I32| b\n";
+    assert_eq!(rename(text, 1, 5, "a"), Ok("f = |a: I32| a\ng = |a: I32| a\n".to_string()));
+}
+
+#[test]
+fn renames_types() {
+    let text = "type Point = (I32, I32)\np: Point = 1, 2\n";
+
+    assert_eq!(rename(text, 1, 3, "Pos"), Ok("type Pos = (I32, I32)\np: Pos = 1, 2\n".to_string()));
+    assert_eq!(rename(text, 0, 5, "Str"), Err("`Str` is a builtin type".to_string()));
+}
+
+#[test]
+fn renames_methods_with_implementations() {
+    let text = "trait Show {\n\tshow: (Self) -> I32\n}\nimpl Show for I32 {\n\tshow = |x| x\n}\nn: I32 = 1\nm = n.show\n";
+
+    assert_eq!(
+        rename(text, 7, 6, "display"),
+        Ok("trait Show {\n\tdisplay: (Self) -> I32\n}\nimpl Show for I32 {\n\tdisplay = |x| x\n}\nn: I32 = 1\nm = n.display\n".to_string()),
+    );
+}
+
+#[test]
+fn prepare_rename() {
+    let text = "x: I32 = 1\n";
+
+    assert_eq!(prepare(text, 0, 0), Ok(Some((range(0, 0, 1), "x".to_string()))));
+    assert_eq!(prepare(text, 0, 4), Err("`I32` is a builtin type".to_string()));
+    assert_eq!(prepare(text, 0, 9), Ok(None));
+}
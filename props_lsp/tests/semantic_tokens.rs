@@ -0,0 +1,138 @@
+mod common;
+
+use lsp_server::Notification;
+use lsp_types::notification::{DidChangeTextDocument, Notification as _};
+use lsp_types::request::{SemanticTokensFullDeltaRequest, SemanticTokensFullRequest};
+use lsp_types::{
+    DidChangeTextDocumentParams, Position, Range, SemanticToken, SemanticTokens, SemanticTokensDeltaParams,
+    SemanticTokensFullDeltaResult, SemanticTokensParams, SemanticTokensResult, TextDocumentContentChangeEvent,
+    TextDocumentIdentifier, VersionedTextDocumentIdentifier,
+};
+use props_lsp::server::Server;
+use common::{open, request, uri};
+
+const TEXT: &str = "// numbers\nadd = |a: I32 b: I32| a + b\nmut n: I32 = add 1 2\ns = \"s\"\n";
+
+fn change(server: &mut Server, version: i32, range: Range, text: &str) {
+    server.handle_notification(Notification::new(DidChangeTextDocument::METHOD.to_string(), DidChangeTextDocumentParams {
+        text_document: VersionedTextDocumentIdentifier::new(uri(), version),
+        content_changes: vec![TextDocumentContentChangeEvent { range: Some(range), range_length: None, text: text.to_string() }],
+    }));
+}
+
+fn full(server: &mut Server) -> SemanticTokens {
+    let params = SemanticTokensParams {
+        text_document: TextDocumentIdentifier::new(uri()),
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+    };
+
+    match request::<SemanticTokensFullRequest>(server, params).unwrap() {
+        Some(SemanticTokensResult::Tokens(tokens)) => tokens,
+        result => panic!("unexpected result {:?}", result),
+    }
+}
+
+fn delta(server: &mut Server, previous_result_id: &str) -> SemanticTokensFullDeltaResult {
+    let params = SemanticTokensDeltaParams {
+        text_document: TextDocumentIdentifier::new(uri()),
+        previous_result_id: previous_result_id.to_string(),
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+    };
+
+    request::<SemanticTokensFullDeltaRequest>(server, params).unwrap().unwrap()
+}
+
+/** The tokens as the integers they are sent as, which delta edits index into **/
+fn encoded(tokens: &[SemanticToken]) -> Vec<u32> {
+    tokens.iter()
+        .flat_map(|token| [token.delta_line, token.delta_start, token.length, token.token_type, token.token_modifiers_bitset])
+        .collect()
+}
+
+/** Each token's line, start, length, type and modifiers, with positions made absolute **/
+fn decoded(tokens: &[SemanticToken]) -> Vec<(u32, u32, u32, String, u32)> {
+    let legend = Server::capabilities().semantic_tokens_provider.map(|provider| match provider {
+        lsp_types::SemanticTokensServerCapabilities::SemanticTokensOptions(options) => options.legend,
+        lsp_types::SemanticTokensServerCapabilities::SemanticTokensRegistrationOptions(options) => options.semantic_tokens_options.legend,
+    }).unwrap();
+    let (mut line, mut start) = (0, 0);
+
+    tokens.iter()
+        .map(|token| {
+            line += token.delta_line;
+            start = if token.delta_line == 0 { start + token.delta_start } else { token.delta_start };
+            let type_ = legend.token_types[token.token_type as usize].as_str().to_string();
+            (line, start, token.length, type_, token.token_modifiers_bitset)
+        })
+        .collect()
+}
+
+#[test]
+fn classifies_tokens() {
+    let tokens = decoded(&full(&mut open(TEXT)).data);
+    let of_line = |line: u32| tokens.iter()
+        .filter(|token| token.0 == line)
+        .map(|(_, start, length, type_, modifiers)| (*start, *length, type_.as_str(), *modifiers))
+        .collect::<Vec<_>>();
+
+    assert_eq!(of_line(0), [(0, 10, "comment", 0)]);
+    assert_eq!(of_line(1), [
+        (0, 3, "function", 3), (4, 1, "operator", 0), (7, 1, "parameter", 3), (10, 3, "type", 0),
+        (14, 1, "parameter", 3), (17, 3, "type", 0), (22, 1, "parameter", 2), (24, 1, "operator", 0), (26, 1, "parameter", 2),
+    ]);
+    // a mutable binding is not readonly
+    assert_eq!(of_line(2), [
+        (0, 3, "keyword", 0), (4, 1, "variable", 1), (7, 3, "type", 0), (11, 1, "operator", 0), (13, 3, "function", 2),
+        (17, 1, "number", 0), (19, 1, "number", 0),
+    ]);
+    assert_eq!(of_line(3), [(0, 1, "variable", 3), (2, 1, "operator", 0), (4, 3, "string", 0)]);
+}
+
+#[test]
+fn delta_applies_to_previous_tokens() {
+    // the mirror gets the same changes and answers in full, which the deltas must add up to
+    let (mut server, mut mirror) = (open(TEXT), open(TEXT));
+    let tokens = full(&mut server);
+    let mut data = encoded(&tokens.data);
+    let mut result_id = tokens.result_id.unwrap();
+
+    let changes = [
+        (Range::new(Position::new(3, 4), Position::new(3, 7)), "1.5"),
+        (Range::new(Position::new(0, 0), Position::new(0, 0)), "x = 1\n"),
+        (Range::new(Position::new(3, 0), Position::new(4, 0)), ""),
+        (Range::new(Position::new(1, 0), Position::new(1, 0)), ""),
+    ];
+    for (version, (range, text)) in changes.into_iter().enumerate() {
+        change(&mut server, version as i32 + 2, range, text);
+        change(&mut mirror, version as i32 + 2, range, text);
+
+        let SemanticTokensFullDeltaResult::TokensDelta(delta) = delta(&mut server, &result_id) else {
+            panic!("expected a delta");
+        };
+        if version == 0 {
+            // only the string turned number changed
+            assert_eq!((delta.edits[0].start, delta.edits[0].delete_count), (data.len() as u32 - 5, 5));
+        }
+
+        for edit in delta.edits.iter().rev() {
+            let start = edit.start as usize;
+            data.splice(start..start + edit.delete_count as usize, encoded(edit.data.as_deref().unwrap_or_default()));
+        }
+        result_id = delta.result_id.unwrap();
+
+        assert_eq!(data, encoded(&full(&mut mirror).data), "after change {}", version);
+    }
+
+    assert!(matches!(delta(&mut server, &result_id), SemanticTokensFullDeltaResult::TokensDelta(delta) if delta.edits.is_empty()));
+}
+
+#[test]
+fn delta_against_replaced_tokens_is_full() {
+    let mut server = open(TEXT);
+    let tokens = full(&mut server);
+    full(&mut server);
+
+    assert!(matches!(delta(&mut server, &tokens.result_id.unwrap()), SemanticTokensFullDeltaResult::Tokens(_)));
+}
@@ -0,0 +1,80 @@
+mod common;
+
+use lsp_types::request::SignatureHelpRequest;
+use lsp_types::{Documentation, ParameterLabel, SignatureHelp, SignatureHelpParams};
+use common::{end, open, request};
+
+const ADD: &str = "/// Adds two numbers\nadd = |a: I32 b: I32| a + b\nneg = |n: I32| 0 - n\n";
+
+/** The signature help with the cursor at the end of a source **/
+fn help(text: &str) -> Option<SignatureHelp> {
+    let params = SignatureHelpParams {
+        context: None,
+        text_document_position_params: end(text),
+        work_done_progress_params: Default::default(),
+    };
+
+    request::<SignatureHelpRequest>(&mut open(text), params).unwrap()
+}
+
+/** The label of the signature, and the parameter labels it highlights **/
+fn signature(help: &SignatureHelp) -> (String, Vec<String>) {
+    let signature = &help.signatures[0];
+    let label: Vec<u16> = signature.label.encode_utf16().collect();
+    let parameters = signature.parameters.iter().flatten().map(|parameter| match parameter.label {
+        ParameterLabel::LabelOffsets([start, end]) => String::from_utf16(&label[start as usize..end as usize]).unwrap(),
+        ParameterLabel::Simple(ref label) => label.clone(),
+    });
+
+    (signature.label.clone(), parameters.collect())
+}
+
+fn active(text: &str) -> Option<u32> {
+    help(&format!("{}{}", ADD, text)).and_then(|help| help.active_parameter)
+}
+
+#[test]
+fn signature_of_callee() {
+    let help = help(&format!("{}x = add ", ADD)).unwrap();
+
+    assert_eq!(signature(&help), ("add a: I32 b: I32 -> I32".to_string(), vec!["a: I32".to_string(), "b: I32".to_string()]));
+    assert_eq!(help.signatures[0].documentation, Some(Documentation::String("Adds two numbers".to_string())));
+}
+
+#[test]
+fn active_parameter_counts_arguments() {
+    assert_eq!(active("x = add "), Some(0));
+    assert_eq!(active("x = add 1"), Some(0));
+    assert_eq!(active("x = add 1 "), Some(1));
+    assert_eq!(active("x = add 1 2"), Some(1));
+    assert_eq!(active("add 1 "), Some(1));
+}
+
+#[test]
+fn operators_do_not_separate_arguments() {
+    assert_eq!(active("x = add 1 + 2"), Some(0));
+    assert_eq!(active("x = add 1 + 2 "), Some(1));
+    assert_eq!(active("x = add (neg 1) "), Some(1));
+}
+
+#[test]
+fn innermost_open_call() {
+    let help = help(&format!("{}x = add 1 (neg ", ADD)).unwrap();
+
+    assert_eq!(signature(&help).0, "neg n: I32 -> I32");
+    assert_eq!(help.active_parameter, Some(0));
+}
+
+#[test]
+fn method_skips_receiver() {
+    let source = "trait Scale {\n\tscale: (Self, I32) -> I32\n}\nimpl Scale for I32 {\n\tscale = |x by| x * by\n}\nn: I32 = 2\nm = n.scale ";
+    let help = help(source).unwrap();
+
+    assert_eq!(signature(&help).1, vec!["by: I32".to_string()]);
+    assert_eq!(help.active_parameter, Some(0));
+}
+
+#[test]
+fn no_help_outside_calls() {
+    assert_eq!(help(&format!("{}x = 1", ADD)), None);
+}
@@ -0,0 +1,162 @@
+mod common;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use lsp_types::request::{DocumentSymbolRequest, WorkspaceSymbolRequest};
+use lsp_types::{
+    DocumentSymbol, DocumentSymbolParams, DocumentSymbolResponse, InitializeParams, OneOf, TextDocumentIdentifier, Uri,
+    WorkspaceFolder, WorkspaceSymbolParams, WorkspaceSymbolResponse,
+};
+use props_lsp::server::Server;
+use common::{open, open_at, request, uri};
+
+fn file_uri(path: &Path) -> Uri {
+    format!("file://{}", path.display()).parse().unwrap()
+}
+
+/** The outline of a source, nested the way it is sent **/
+fn document_symbols(text: &str) -> Vec<DocumentSymbol> {
+    let params = DocumentSymbolParams {
+        text_document: TextDocumentIdentifier::new(uri()),
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+    };
+
+    match request::<DocumentSymbolRequest>(&mut open(text), params).unwrap() {
+        Some(DocumentSymbolResponse::Nested(items)) => items,
+        response => panic!("expected a nested outline: {:?}", response),
+    }
+}
+
+/** The outline as the name, kind and detail of each item, children indented below their parent **/
+fn outline(text: &str) -> Vec<String> {
+    fn lines(items: &[DocumentSymbol], depth: usize, out: &mut Vec<String>) {
+        for item in items {
+            out.push(format!("{}{} {:?} {}", "  ".repeat(depth), item.name, item.kind, item.detail.as_deref().unwrap_or("")).trim_end().to_string());
+            lines(item.children.as_deref().unwrap_or_default(), depth + 1, out);
+        }
+    }
+
+    let mut out = vec![];
+    lines(&document_symbols(text), 0, &mut out);
+    out
+}
+
+/** A server with a folder as its workspace **/
+fn workspace(folder: &Path) -> Server {
+    let mut server = Server::new();
+    server.initialize(InitializeParams {
+        workspace_folders: Some(vec![WorkspaceFolder { uri: file_uri(folder), name: "props".to_string() }]),
+        ..Default::default()
+    });
+    server
+}
+
+/** The workspace symbols matching a query, as their name, container and file name **/
+fn search(server: &mut Server, query: &str) -> Vec<(String, Option<String>, String)> {
+    let params = WorkspaceSymbolParams {
+        query: query.to_string(),
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+    };
+    let file = |uri: &Uri| uri.as_str().rsplit('/').next().unwrap().to_string();
+
+    // the response is untagged, so symbols with plain locations read back as flat symbol information
+    let mut symbols: Vec<_> = match request::<WorkspaceSymbolRequest>(server, params).unwrap() {
+        Some(WorkspaceSymbolResponse::Flat(symbols)) => symbols.into_iter()
+            .map(|symbol| (symbol.name, symbol.container_name, file(&symbol.location.uri)))
+            .collect(),
+        Some(WorkspaceSymbolResponse::Nested(symbols)) => symbols.into_iter()
+            .map(|symbol| {
+                let OneOf::Left(location) = symbol.location else {
+                    panic!("expected a location");
+                };
+                (symbol.name, symbol.container_name, file(&location.uri))
+            })
+            .collect(),
+        None => vec![],
+    };
+    symbols.sort();
+    symbols
+}
+
+/** An empty folder of its own for a test **/
+fn folder(name: &str) -> PathBuf {
+    let folder = std::env::temp_dir().join(format!("props_lsp_{}_{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&folder);
+    fs::create_dir_all(&folder).unwrap();
+    folder
+}
+
+#[test]
+fn nested_outline() {
+    let text = "type Point = (I32, I32)\ntrait Show {\n\tshow: (Self) -> I32\n}\nimpl Show for I32 {\n\tshow = |x| x\n}\nadd = |a: I32 b: I32| {\n\tc = a + b\n\treturn c\n}\nx, mut y = 1, \"s\"\n";
+
+    assert_eq!(outline(text), [
+        "Point Struct (I32, I32)",
+        "Show Interface",
+        "  show Method (Self) -> I32",
+        "impl Show for I32 Object",
+        "  show Method (I32) -> I32",
+        "add Function (I32, I32) -> I32",
+        "  c Variable I32",
+        "x Variable I16",
+        "y Variable Str",
+    ]);
+}
+
+#[test]
+fn outline_ranges_cover_bodies() {
+    let items = document_symbols("f = |a: I32| {\n\tb = a\n\treturn b\n}\n");
+
+    assert_eq!((items[0].range.start.line, items[0].range.end.line), (0, 3));
+    assert_eq!((items[0].selection_range.start.line, items[0].selection_range.end.character), (0, 1));
+}
+
+#[test]
+fn workspace_symbols_across_files() {
+    let folder = folder("search");
+    fs::create_dir_all(folder.join("nested")).unwrap();
+    fs::create_dir_all(folder.join(".hidden")).unwrap();
+    fs::write(folder.join("a.prop"), "add = |a: I32 b: I32| {\n\tsum = a + b\n\treturn sum\n}\n").unwrap();
+    fs::write(folder.join("nested/b.prop"), "address = \"here\"\n").unwrap();
+    fs::write(folder.join(".hidden/c.prop"), "adder = 1\n").unwrap();
+    fs::write(folder.join("d.txt"), "added = 1\n").unwrap();
+
+    let mut server = workspace(&folder);
+
+    let symbol = |name: &str, container: Option<&str>, file: &str| (name.to_string(), container.map(str::to_string), file.to_string());
+    assert_eq!(search(&mut server, "ad"), [symbol("add", None, "a.prop"), symbol("address", None, "b.prop")]);
+    // the characters of the query only need to appear in order
+    assert_eq!(search(&mut server, "SM"), [symbol("sum", Some("add"), "a.prop")]);
+
+    // an open document takes the place of its file
+    open_at(&mut server, file_uri(&folder.join("a.prop")), "total = 1\n");
+    assert_eq!(search(&mut server, "t"), [symbol("total", None, "a.prop")]);
+
+    // deleted files are not searched anymore
+    fs::remove_file(folder.join("nested/b.prop")).unwrap();
+    assert!(search(&mut server, "address").is_empty());
+
+    // files added after a search are found by the next one
+    fs::write(folder.join("nested/e.prop"), "extra = 1\n").unwrap();
+    assert_eq!(search(&mut server, "extra"), [symbol("extra", None, "e.prop")]);
+
+    fs::remove_dir_all(folder).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn linked_folders_are_not_followed() {
+    let folder = folder("links");
+    fs::create_dir_all(folder.join("nested")).unwrap();
+    fs::write(folder.join("nested/a.prop"), "add = 1\n").unwrap();
+    // a link back to the workspace would otherwise be walked forever
+    std::os::unix::fs::symlink("..", folder.join("nested/loop")).unwrap();
+
+    let mut server = workspace(&folder);
+
+    assert_eq!(search(&mut server, "add"), [("add".to_string(), None, "a.prop".to_string())]);
+
+    fs::remove_dir_all(folder).unwrap();
+}
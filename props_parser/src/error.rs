@@ -1,8 +1,8 @@
 use colored::Colorize;
-use log::error;
 use thiserror::Error;
 use crate::tokens::Token;
-use crate::types::Type;
+use crate::nodes::Span;
+use crate::types::{PrimitiveType, Type};
 use crate::util::Access;
 
 #[derive(Error, Debug)]
@@ -13,29 +13,256 @@ pub enum ParserErr {
         pos: usize,
         token: Token,
     },
+    #[error("Unexpected end of file after line {}", span.line + 1)]
+    UnexpectedEof {
+        span: Span,
+    },
     #[error("Can not assign type {type_1} to an identifier of type {type_2}")]
     UnmatchedTypes {
         type_1: Access<Type>,
         type_2: Access<Type>,
-    }
+        span: Span,
+    },
+    #[error("Literal {literal} does not fit in {type_}, expected a value from {min} to {max}")]
+    LiteralOutOfRange {
+        literal: String,
+        type_: PrimitiveType,
+        min: String,
+        max: String,
+        span: Span,
+    },
+    #[error("Can not destructure {found} values into {expected} identifiers")]
+    UnmatchedArity {
+        expected: usize,
+        found: usize,
+        span: Span,
+    },
+    #[error("Can not destructure type {type_} into {expected} identifiers")]
+    NotCompound {
+        type_: Access<Type>,
+        expected: usize,
+        span: Span,
+    },
+    #[error("Binding {name} is not defined")]
+    UndefinedBinding {
+        name: String,
+        span: Span,
+    },
+    #[error("Type {name} is not defined")]
+    UndefinedType {
+        name: String,
+        span: Span,
+    },
+    #[error("Type {name} takes {expected} type arguments but {found} were given")]
+    TypeArgCount {
+        name: String,
+        expected: usize,
+        found: usize,
+        span: Span,
+    },
+    #[error("Can not call a value of type {type_}")]
+    NotCallable {
+        type_: Access<Type>,
+        span: Span,
+    },
+    #[error("Function takes {expected} arguments but {found} were given")]
+    ArgumentCount {
+        expected: usize,
+        found: usize,
+        span: Span,
+    },
+    #[error("Can not pass type {found} to a parameter of type {expected}")]
+    UnmatchedArgument {
+        expected: Access<Type>,
+        found: Access<Type>,
+        span: Span,
+    },
+    #[error("Trait {name} is not defined")]
+    UndefinedTrait {
+        name: String,
+        span: Span,
+    },
+    #[error("Trait {trait_name} is already implemented for type {type_}")]
+    ConflictingImpl {
+        trait_name: String,
+        type_: Access<Type>,
+        span: Span,
+    },
+    #[error("{method} is not a method of trait {trait_name}")]
+    UnknownMethod {
+        trait_name: String,
+        method: String,
+        span: Span,
+    },
+    #[error("Implementation of trait {trait_name} for type {type_} is missing method {method}")]
+    MissingMethod {
+        trait_name: String,
+        method: String,
+        type_: Access<Type>,
+        span: Span,
+    },
+    #[error("Type {type_} does not implement a trait with method {method}")]
+    MissingImpl {
+        method: String,
+        type_: Access<Type>,
+        span: Span,
+    },
+    #[error("Type {type_} has no field or method {name}")]
+    UnknownField {
+        name: String,
+        type_: Access<Type>,
+        span: Span,
+    },
+    #[error("Can not apply an operator to {lhs} and {rhs}, cast one operand to the type of the other")]
+    IncompatibleOperands {
+        lhs: Access<Type>,
+        rhs: Access<Type>,
+        span: Span,
+    },
+    #[error("Can not cast type {from} to {to}")]
+    InvalidCast {
+        from: Access<Type>,
+        to: Access<Type>,
+        span: Span,
+    },
+    #[error("Value of optional type {type_} may be none, use `??` or `?.` to handle it")]
+    UnwrappedOptional {
+        type_: Access<Type>,
+        span: Span,
+    },
+    #[error("Can not propagate errors from type {type_}, expected a Result")]
+    NotResult {
+        type_: Access<Type>,
+        span: Span,
+    },
+    #[error("Errors can only be propagated with `?` inside a function")]
+    PropagationOutsideFunction {
+        span: Span,
+    },
+    #[error("Can not propagate errors from a function returning {type_}")]
+    InvalidPropagation {
+        type_: Access<Type>,
+        span: Span,
+    },
+    #[error("Can not propagate error type {found} from a function returning errors of type {expected}")]
+    PropagatedErrorType {
+        expected: Access<Type>,
+        found: Access<Type>,
+        span: Span,
+    },
+    #[error("Can not reassign immutable binding {name}, declare it with `mut` to allow reassignment")]
+    ImmutableReassignment {
+        name: String,
+        span: Span,
+        previous: Span,
+    },
+    #[error("Can not use the result of a call returning Unit as a value")]
+    UnitValue {
+        span: Span,
+    },
+    #[error("Can not make an impure call inside a function declared pure")]
+    ImpureCall {
+        span: Span,
+    },
+    #[error("Can not reassign {name} of type {expected} with a value of type {found}")]
+    ReassignedType {
+        name: String,
+        expected: Access<Type>,
+        found: Access<Type>,
+        span: Span,
+        previous: Span,
+    },
 }
 
 impl ParserErr {
-    pub fn print(&self, source: &Vec<String>) {
-        println!();
-        println!("Parsing Error: {}", self.to_string().red());
+    /**
+     * Prints the error with the source line it points at to stderr
+     **/
+    pub fn print(&self, source: &[String]) {
+        eprintln!();
+        eprintln!("Parsing Error: {}", self.to_string().red());
+
+        ParserErr::print_line(source, self.span());
+
+        if let ParserErr::ImmutableReassignment { previous, .. } | ParserErr::ReassignedType { previous, .. } = self {
+            eprintln!("First assigned here:");
+            ParserErr::print_line(source, *previous);
+        }
+    }
+
+    /**
+     * The region of source the error points at
+     **/
+    pub fn span(&self) -> Span {
+        match self {
+            ParserErr::UnexpectedToken { line, pos, token } => Span::new(line - 1, (pos + 1).saturating_sub(token.len()), pos + 1),
+            ParserErr::UnexpectedEof { span } |
+            ParserErr::UnmatchedTypes { span, .. } |
+            ParserErr::LiteralOutOfRange { span, .. } |
+            ParserErr::UnmatchedArity { span, .. } |
+            ParserErr::NotCompound { span, .. } |
+            ParserErr::UndefinedBinding { span, .. } |
+            ParserErr::UndefinedType { span, .. } |
+            ParserErr::TypeArgCount { span, .. } |
+            ParserErr::NotCallable { span, .. } |
+            ParserErr::ArgumentCount { span, .. } |
+            ParserErr::UnmatchedArgument { span, .. } |
+            ParserErr::UndefinedTrait { span, .. } |
+            ParserErr::ConflictingImpl { span, .. } |
+            ParserErr::UnknownMethod { span, .. } |
+            ParserErr::MissingMethod { span, .. } |
+            ParserErr::MissingImpl { span, .. } |
+            ParserErr::UnknownField { span, .. } |
+            ParserErr::IncompatibleOperands { span, .. } |
+            ParserErr::InvalidCast { span, .. } |
+            ParserErr::UnwrappedOptional { span, .. } |
+            ParserErr::NotResult { span, .. } |
+            ParserErr::PropagationOutsideFunction { span } |
+            ParserErr::InvalidPropagation { span, .. } |
+            ParserErr::PropagatedErrorType { span, .. } |
+            ParserErr::UnitValue { span } |
+            ParserErr::ImpureCall { span } |
+            ParserErr::ImmutableReassignment { span, .. } |
+            ParserErr::ReassignedType { span, .. } => *span,
+        }
+    }
+
+    /**
+     * Moves the error by a number of lines, for a statement kept while lines before it were edited.
+     * Only parsing errors are kept, typing errors are found again
+     **/
+    pub(crate) fn shift(&mut self, lines: isize) {
+        match self {
+            ParserErr::UnexpectedToken { line, .. } => *line = line.wrapping_add_signed(lines),
+            ParserErr::UnexpectedEof { span } => span.shift(lines),
+            _ => {}
+        }
+    }
+
+    /**
+     * Reports a literal as it was written rather than as the value it was parsed to,
+     * which can have lost digits or be infinite
+     **/
+    pub(crate) fn attach_source(&mut self, source: &[String]) {
+        if let ParserErr::LiteralOutOfRange { literal, span, .. } = self {
+            if let Some(text) = source.get(span.line).and_then(|line| line.get(span.start..span.end)) {
+                let sign = if literal.starts_with('-') { "-" } else { "" };
+                *literal = format!("{}{}", sign, text);
+            }
+        }
+    }
 
-        let (line, pos, token) = match self {
-            ParserErr::UnexpectedToken { line, pos, token } => (line, pos, token),
-            _ => (&0, &0, &Token::Newline)
+    fn print_line(source: &[String], span: Span) {
+        let Some(text) = source.get(span.line) else {
+            return;
         };
 
-        let text = &source[*line - 1];
-        println!("  | ");
-        println!("{} | {}", line.to_string().blue(), text);
+        let line = span.line + 1;
+        eprintln!("  | ");
+        eprintln!("{} | {}", line.to_string().blue(), text);
 
-        let pointer: String = " ".repeat(if pos > &0 { pos - token.len() + 1 } else { 0 } ) + &"^".repeat(token.len());
-        println!("  | {}", pointer.red());
-        println!();
+        let pointer: String = " ".repeat(span.start) + &"^".repeat(span.len());
+        eprintln!("  | {}", pointer.red());
+        eprintln!();
     }
 }
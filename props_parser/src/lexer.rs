@@ -1,6 +1,7 @@
 use crate::tokens::Token;
 use lazy_static::lazy_static;
 use regex::Regex;
+use crate::nodes::Span;
 use crate::types::Number;
 
 lazy_static! {
@@ -9,8 +10,6 @@ lazy_static! {
     static ref NUMBER_REGEX: Regex = Regex::new(r"-?[0-9.]").unwrap();
 }
 
-const RETURN_WORD_LEN: usize = 6;
-
 pub struct Lexer;
 
 impl Lexer {
@@ -44,6 +43,17 @@ impl Lexer {
             ':' => (Token::TypeAnnotator, i),
             ',' => (Token::Comma, i),
             '.' => (Token::Period, i),
+            '?' => {
+                if Lexer::is(chars, i + 1, '?') {
+                    return Some((Token::Coalesce, i + 1));
+                }
+
+                if Lexer::is(chars, i + 1, '.') {
+                    return Some((Token::SafeAccess, i + 1));
+                }
+
+                (Token::Question, i)
+            }
             '"' => {
                 let mut str = Vec::new();
                 let mut new_i = i + 1;
@@ -56,6 +66,26 @@ impl Lexer {
                 // not new_i - 1 to consume the ending "
                 return Some((Token::StringLiteral(str.iter().collect()), new_i));
             }
+            '\'' => {
+                let mut new_i = i + 1;
+
+                while Lexer::is_match(chars, new_i, |nc| nc != '\'') {
+                    // skip escaped characters, e.g. '\''
+                    if chars[new_i] == b'\\' {
+                        new_i += 1;
+                    }
+
+                    new_i += 1;
+                }
+
+                let text = std::str::from_utf8(&chars[i + 1..new_i.min(chars.len())]).unwrap_or_default();
+                let token = match Lexer::unescape_char(text) {
+                    Some(c) => Token::CharLiteral(c),
+                    None => Token::Unknown(format!("'{}'", text)),
+                };
+
+                return Some((token, new_i));
+            }
             '{' => (Token::FuncOpen, i),
             '}' => (Token::FuncClose, i),
             '=' => {
@@ -66,7 +96,13 @@ impl Lexer {
                 (Token::Assignment, i)
             }
             '+' => (Token::Addition, i),
-            '-' => (Token::Subtraction, i),
+            '-' => {
+                if Lexer::is(chars, i + 1, '>') {
+                    return Some((Token::Arrow, i + 1));
+                }
+
+                (Token::Subtraction, i)
+            }
             '*' => (Token::Multiplication, i),
             '/' => {
                 if Lexer::is(chars, i + 1, '/') {
@@ -76,8 +112,7 @@ impl Lexer {
                         new_i += 1;
                     }
 
-                    // + 1 because not directly returning therefore not advancing
-                    return Lexer::next_token(chars, new_i + 1);
+                    return Some((Token::Comment, new_i - 1));
                 }
 
                 (Token::Division, i)
@@ -113,21 +148,34 @@ impl Lexer {
 
                 (Token::Indent(level), new_i - 1)
             }
-            'r' => {
-                if i + RETURN_WORD_LEN < chars.len() {
-                    let word = &chars[i..i + RETURN_WORD_LEN];
-                    return match std::str::from_utf8(word) {
-                        Ok(str) => Some(if str == "return" { (Token::Return, i + RETURN_WORD_LEN) } else { Lexer::tokenize_else(c, chars, i) }),
-                        Err(_) => Some(Lexer::tokenize_else(c, chars, i))
-                    };
-                }
-
-                Lexer::tokenize_else(c, chars, i)
-            }
             _ => Lexer::tokenize_else(c, chars, i)
         })
     }
 
+    /**
+     * The character written between the quotes of a char literal, `None` unless it is exactly one character
+     **/
+    fn unescape_char(text: &str) -> Option<char> {
+        let mut chars = text.chars();
+        let c = match chars.next()? {
+            '\\' => match chars.next()? {
+                'n' => '\n',
+                't' => '\t',
+                'r' => '\r',
+                '0' => '\0',
+                '\\' => '\\',
+                '\'' => '\'',
+                _ => return None,
+            },
+            c => c,
+        };
+
+        match chars.next() {
+            Some(_) => None,
+            None => Some(c),
+        }
+    }
+
     fn tokenize_else(c: char, chars: &[u8], i: usize) -> (Token, usize) {
         let str = c.to_string();
 
@@ -140,7 +188,21 @@ impl Lexer {
                 new_i += 1;
             }
 
-            return (Token::Ident(ident.iter().collect()), new_i - 1);
+            let ident: String = ident.iter().collect();
+            let token = match ident.as_str() {
+                "return" => Token::Return,
+                "as" => Token::As,
+                "type" => Token::Type,
+                "trait" => Token::Trait,
+                "impl" => Token::Impl,
+                "for" => Token::For,
+                "mut" => Token::Mut,
+                "impure" => Token::Impure,
+                "none" => Token::None,
+                _ => Token::Ident(ident),
+            };
+
+            return (token, new_i - 1);
         }
 
         if NUMBER_REGEX.is_match(&str) {
@@ -169,6 +231,16 @@ impl Lexer {
         }
 
         let number_str: String = number.iter().collect();
+
+        // type suffix, e.g. `10n` or `0.1d`
+        if let Some(suffix @ ('n' | 'd')) = Lexer::get_char(chars, new_i) {
+            if !Lexer::is_match(chars, new_i + 1, |nc| IDENT_REGEX.is_match(&nc.to_string())) {
+                return Number::parse_suffixed(&number_str, suffix)
+                    .ok()
+                    .map(|num| (Token::Number(num), new_i));
+            }
+        }
+
         if let Ok(num) = Number::parse_number(&number_str, has_decimal) {
             return Some((Token::Number(num), new_i - 1));
         }
@@ -176,30 +248,61 @@ impl Lexer {
         None
     }
 
-    pub fn lex(source: &str) -> Vec<(Token, usize)> {
-        let mut tokens = Vec::<(Token, usize)>::new();
-        let mut lines = source.lines();
+    pub fn lex(source: &str) -> Vec<(Token, Span)> {
+        Lexer::lex_with_comments(source)
+            .into_iter()
+            .filter(|(token, _)| token != &Token::Comment)
+            .collect()
+    }
 
-        while let Some(line) = lines.next() {
-            let mut i = 0;
-            let chars = line.as_bytes();
+    /**
+     * Lexes the source keeping comments, for tools which show the source as written
+     **/
+    pub fn lex_with_comments(source: &str) -> Vec<(Token, Span)> {
+        let mut tokens = Vec::<(Token, Span)>::new();
 
-            loop {
-                let token = Lexer::next_token(chars, i);
+        for (line_num, line) in source.lines().enumerate() {
+            Lexer::lex_line_into(line, line_num, &mut tokens);
+        }
 
-                if token.is_none() {
-                    break;
-                }
+        tokens
+    }
+
+    /**
+     * Lexes a single line of source without its line break, which lexes the same on its own as within the whole source
+     **/
+    pub fn lex_line(line: &str, line_num: usize) -> Vec<(Token, Span)> {
+        let mut tokens = Lexer::lex_line_with_comments(line, line_num);
+        tokens.retain(|(token, _)| token != &Token::Comment);
+        tokens
+    }
+
+    /**
+     * Lexes a single line keeping comments, so tools can lex again only the lines which were edited
+     **/
+    pub fn lex_line_with_comments(line: &str, line_num: usize) -> Vec<(Token, Span)> {
+        let mut tokens = vec![];
+        Lexer::lex_line_into(line, line_num, &mut tokens);
+        tokens
+    }
+
+    fn lex_line_into(line: &str, line_num: usize, tokens: &mut Vec<(Token, Span)>) {
+        let mut i = 0;
+        let chars = line.as_bytes();
 
-                let token = token.unwrap();
+        loop {
+            let token = Lexer::next_token(chars, i);
 
-                i = token.1 + 1;
-                tokens.push(token);
+            if token.is_none() {
+                break;
             }
 
-            tokens.push((Token::Newline, i));
+            let (token, end) = token.unwrap();
+
+            tokens.push((token, Span::new(line_num, i, end + 1)));
+            i = end + 1;
         }
 
-        tokens
+        tokens.push((Token::Newline, Span::new(line_num, i, i + 1)));
     }
 }
\ No newline at end of file
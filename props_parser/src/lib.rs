@@ -1,8 +1,12 @@
-mod lexer;
-mod tokens;
+// type errors carry the types involved for diagnostics, which makes ParserErr large
+#![allow(clippy::result_large_err)]
+
+pub mod lexer;
+pub mod tokens;
 pub mod types;
 pub mod error;
 pub mod nodes;
 pub mod pipeline;
 pub mod parser;
+pub mod symbols;
 mod util;
\ No newline at end of file
@@ -1,62 +1,209 @@
 use std::rc::Rc;
 use crate::types::{Number, Type};
 
+/**
+ * A region of a single source line, `line` and `start` are 0 based and `end` is exclusive
+ **/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Span {
+    pub line: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(line: usize, start: usize, end: usize) -> Self {
+        Span { line, start, end }
+    }
+
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    /**
+     * Spans from the start of this span to the end of the other, or just this span if they are on different lines
+     **/
+    pub fn to(&self, other: Span) -> Span {
+        if self.line != other.line {
+            return *self;
+        }
+
+        Span::new(self.line, self.start, other.end)
+    }
+
+    pub fn contains(&self, line: usize, pos: usize) -> bool {
+        self.line == line && self.start <= pos && pos < self.end
+    }
+
+    /**
+     * Moves the span down by a number of lines, or up if it is negative
+     **/
+    pub(crate) fn shift(&mut self, lines: isize) {
+        self.line = self.line.wrapping_add_signed(lines);
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum AstNode {
     Assignment(Identifier, Expression),
     ImpFuncCall(Identifier, Vec<Expression>),
-    Return(Expression),
+    Return(Expression, Span),
     Expression(Expression),
+    TypeAlias(String, Vec<String>, Type, Span),
+    Trait(String, Vec<Identifier>, Span),
+    Impl(String, Type, Vec<AstNode>, Span),
+}
+
+impl AstNode {
+    /**
+     * Moves every span in the node by a number of lines, for a statement kept while lines before it were edited
+     **/
+    pub(crate) fn shift(&mut self, lines: isize) {
+        match self {
+            AstNode::Assignment(ident, expr) => {
+                ident.shift(lines);
+                expr.shift(lines);
+            }
+            AstNode::ImpFuncCall(ident, args) => {
+                ident.shift(lines);
+                args.iter_mut().for_each(|arg| arg.shift(lines));
+            }
+            AstNode::Return(expr, span) => {
+                expr.shift(lines);
+                span.shift(lines);
+            }
+            AstNode::Expression(expr) => expr.shift(lines),
+            AstNode::TypeAlias(_, _, _, span) => span.shift(lines),
+            AstNode::Trait(_, methods, span) => {
+                methods.iter_mut().for_each(|method| method.shift(lines));
+                span.shift(lines);
+            }
+            AstNode::Impl(_, _, methods, span) => {
+                methods.iter_mut().for_each(|method| method.shift(lines));
+                span.shift(lines);
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Expression {
     MathExpr(MathExpr),
     StrLiteral(String),
+    CharLiteral(char),
     Compound(Vec<Expression>),
     FuncLiteral {
-        params: Vec<(String, Type)>,
+        params: Vec<Identifier>,
         statements: Vec<AstNode>,
         return_type: Type,
+        // the opening `|` or `{` and the last token of the body
+        span: Span,
+        end: Span,
     },
 }
 
 impl Expression {
-    pub fn compound(self, other: Expression) -> Expression {
-        if let Expression::Compound(mut v) = self {
-            v.push(other);
-            return Expression::Compound(v);
+    pub(crate) fn shift(&mut self, lines: isize) {
+        match self {
+            Expression::MathExpr(expr) => expr.shift(lines),
+            Expression::StrLiteral(_) | Expression::CharLiteral(_) => {}
+            Expression::Compound(exprs) => exprs.iter_mut().for_each(|expr| expr.shift(lines)),
+            Expression::FuncLiteral { params, statements, span, end, .. } => {
+                params.iter_mut().for_each(|param| param.shift(lines));
+                statements.iter_mut().for_each(|statement| statement.shift(lines));
+                span.shift(lines);
+                end.shift(lines);
+            }
         }
-
-        Expression::Compound(vec![self, other])
     }
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Identifier {
-    Identifier(Rc<String>, Rc<Type>),
+    Identifier(Rc<String>, Rc<Type>, Span),
     Accessor(Box<Identifier>, Box<Identifier>),
+    SafeAccessor(Box<Identifier>, Box<Identifier>),
     Compound(Vec<Identifier>),
+    Mutable(Box<Identifier>),
 }
 
 impl Identifier {
-    pub fn compound(self, other: Identifier) -> Identifier {
-        if let Identifier::Compound(mut v) = self {
-            v.push(other);
-            return Identifier::Compound(v);
+    pub fn span(&self) -> Span {
+        match self {
+            Identifier::Identifier(_, _, span) => *span,
+            Identifier::Accessor(lhs, rhs) |
+            Identifier::SafeAccessor(lhs, rhs) => lhs.span().to(rhs.span()),
+            Identifier::Compound(idents) => idents[0].span().to(idents[idents.len() - 1].span()),
+            Identifier::Mutable(ident) => ident.span(),
+        }
+    }
+
+    pub(crate) fn shift(&mut self, lines: isize) {
+        match self {
+            Identifier::Identifier(_, _, span) => span.shift(lines),
+            Identifier::Accessor(lhs, rhs) |
+            Identifier::SafeAccessor(lhs, rhs) => {
+                lhs.shift(lines);
+                rhs.shift(lines);
+            }
+            Identifier::Compound(idents) => idents.iter_mut().for_each(|ident| ident.shift(lines)),
+            Identifier::Mutable(ident) => ident.shift(lines),
         }
-        
-        Identifier::Compound(vec![self, other])
     }
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum MathExpr {
-    Literal(Number),
+    Literal(Number, Span),
     Identifier(Identifier),
     BinaryOp(Box<MathExpr>, Box<MathExpr>, MathOp),
     Negate(Box<MathExpr>),
     FuncCall(Identifier, Vec<Expression>),
+    Cast(Box<MathExpr>, Type, Span),
+    None(Span),
+    Coalesce(Box<MathExpr>, Box<Expression>),
+    Try(Box<MathExpr>, Span),
+}
+
+impl MathExpr {
+    pub fn span(&self) -> Span {
+        match self {
+            MathExpr::Literal(_, span) | MathExpr::None(span) => *span,
+            MathExpr::Identifier(ident) | MathExpr::FuncCall(ident, _) => ident.span(),
+            MathExpr::BinaryOp(lhs, rhs, _) => lhs.span().to(rhs.span()),
+            MathExpr::Coalesce(lhs, rhs) => match rhs.as_ref() {
+                Expression::MathExpr(rhs) => lhs.span().to(rhs.span()),
+                _ => lhs.span(),
+            },
+            MathExpr::Negate(expr) => expr.span(),
+            MathExpr::Cast(expr, _, span) | MathExpr::Try(expr, span) => expr.span().to(*span),
+        }
+    }
+
+    pub(crate) fn shift(&mut self, lines: isize) {
+        match self {
+            MathExpr::Literal(_, span) | MathExpr::None(span) => span.shift(lines),
+            MathExpr::Identifier(ident) => ident.shift(lines),
+            MathExpr::BinaryOp(lhs, rhs, _) => {
+                lhs.shift(lines);
+                rhs.shift(lines);
+            }
+            MathExpr::Negate(expr) => expr.shift(lines),
+            MathExpr::FuncCall(ident, args) => {
+                ident.shift(lines);
+                args.iter_mut().for_each(|arg| arg.shift(lines));
+            }
+            MathExpr::Cast(expr, _, span) | MathExpr::Try(expr, span) => {
+                expr.shift(lines);
+                span.shift(lines);
+            }
+            MathExpr::Coalesce(lhs, rhs) => {
+                lhs.shift(lines);
+                rhs.shift(lines);
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
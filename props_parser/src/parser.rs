@@ -1,10 +1,11 @@
-use std::collections::VecDeque;
+use std::cell::Cell;
+use std::ops::Range;
 use std::rc::Rc;
 use crate::error::ParserErr;
 use crate::tokens::Token;
 use crate::lexer::Lexer;
-use crate::nodes::{AstNode, Expression, Identifier, MathExpr, MathOp};
-use crate::types::Type;
+use crate::nodes::{AstNode, Expression, Identifier, MathExpr, MathOp, Span};
+use crate::types::{Effect, Type};
 use crate::util::Access;
 
 /**
@@ -16,20 +17,18 @@ macro_rules! expect {
             if $skip_empty {
                 $self.skip_empty();
             }
-            
-            let line = $self.line;
-            
+
             match $self.next() {
                 $(Some(($pat, _)) => $expr,)*
                 #[allow(unreachable_patterns)]
-                Some((token, pos)) => {
+                Some((token, span)) => {
                     Err(ParserErr::UnexpectedToken {
-                        line: line + 1,
-                        pos: pos,
-                        token: token,
+                        line: span.line + 1,
+                        pos: span.end - 1,
+                        token,
                     })
                 }
-                None => panic!("This should not happen")
+                None => Err(ParserErr::UnexpectedEof { span: $self.prev_span })
             }
         }
     };
@@ -62,64 +61,351 @@ macro_rules! peek_match_ignore_ws {
     };
 }
 
+/**
+ * The tokens of each source line, read from a cursor which remembers the furthest token looked at
+ **/
+#[derive(Debug, Default)]
+struct TokenStream {
+    lines: Vec<Vec<(Token, Span)>>,
+    // the line of the next token and its index within the line
+    cursor: (usize, usize),
+    furthest: Cell<(usize, usize)>,
+}
+
+impl TokenStream {
+    fn get(&self, skip: usize) -> Option<&(Token, Span)> {
+        let (mut line, mut index) = self.cursor;
+        index += skip;
+
+        while line < self.lines.len() && index >= self.lines[line].len() {
+            index -= self.lines[line].len();
+            line += 1;
+        }
+
+        // looking past the last token depends on the source ending there
+        let position = if line < self.lines.len() { (line, index) } else { (line, 0) };
+        self.furthest.set(self.furthest.get().max(position));
+
+        self.lines.get(line).map(|tokens| &tokens[index])
+    }
+
+    fn front(&self) -> Option<&(Token, Span)> {
+        self.get(0)
+    }
+
+    fn pop_front(&mut self) -> Option<(Token, Span)> {
+        let token = self.front()?.clone();
+        let (line, index) = self.cursor;
+
+        self.cursor = if index + 1 < self.lines[line].len() { (line, index + 1) } else { (line + 1, 0) };
+        Some(token)
+    }
+
+    /**
+     * Steps back over the last token taken
+     **/
+    fn unread(&mut self) {
+        let (line, index) = self.cursor;
+
+        self.cursor = match index {
+            0 => (line - 1, self.lines[line - 1].len() - 1),
+            _ => (line, index - 1),
+        };
+    }
+}
+
+/**
+ * What parsing a statement depends on besides the tokens it looks at
+ **/
+#[derive(Debug, Clone, PartialEq, Default)]
+struct ParseState {
+    prev_span: Span,
+    parsing_ws_delim: bool,
+    ws_delim_in_parenth: bool,
+}
+
+/**
+ * A top level statement as it was parsed, kept to reuse it when the source changes where it did not look
+ **/
+#[derive(Debug, Clone)]
+struct Statement {
+    start: (usize, usize),
+    // the furthest token looked at, on the line after the last if it looked for more tokens
+    furthest: (usize, usize),
+    state: ParseState,
+    nodes: usize,
+    errs: usize,
+}
+
 #[derive(Debug)]
 pub struct PropsParser {
-    tokens: VecDeque<(Token, usize)>,
+    tokens: TokenStream,
     source: Vec<String>,
-    line: usize,
+    prev_span: Span,
     parsing_ws_delim: bool,
     ws_delim_in_parenth: bool,
+    // errors recovered from while still producing a node, e.g. in an unfinished function body
+    recovered: Vec<ParserErr>,
+    statements: Vec<Statement>,
 }
 
 #[allow(dead_code)]
 impl PropsParser {
     pub fn new() -> Self {
         PropsParser {
-            tokens: VecDeque::new(),
+            tokens: TokenStream::default(),
             source: vec![],
-            line: 0,
+            prev_span: Span::default(),
             parsing_ws_delim: false,
             ws_delim_in_parenth: false,
+            recovered: vec![],
+            statements: vec![],
         }
     }
     
     pub fn init(&mut self, source: String) {
-        let tokens = Lexer::lex(&source);
-        self.tokens = VecDeque::from(tokens);
+        self.tokens = TokenStream {
+            lines: source.lines().enumerate().map(|(line_num, line)| Lexer::lex_line(line, line_num)).collect(),
+            ..TokenStream::default()
+        };
         self.source = source.lines().map(String::from).collect();
+        self.restore(&ParseState::default());
+        self.recovered.clear();
+    }
+
+    /**
+     * The lines of the source being parsed
+     **/
+    pub fn source(&self) -> &[String] {
+        &self.source
     }
 
     pub fn parse(&mut self) -> (Vec<AstNode>, Vec<ParserErr>) {
         let mut result = Vec::new();
         let mut errs = Vec::new();
 
-        loop {
-            // skip all starting empty tokens
-            self.skip_empty();
+        self.statements.clear();
+        while self.parse_statement(&mut result, &mut errs) {}
 
-            match self.parse_node() {
-                Ok(Some(node)) => result.push(node),
-                Ok(None) => break,
-                Err(err) => {
-                    err.print(&self.source);
-                    errs.push(err);
+        (result, errs)
+    }
+
+    /**
+     * Replaces a range of lines of the parsed source, updating the nodes and errors the last parse produced.
+     * Only statements which looked at the replaced lines are parsed again, the ones after them are moved to their new lines
+     **/
+    pub fn reparse(&mut self, lines: Range<usize>, source: Vec<String>, result: &mut Vec<AstNode>, errs: &mut Vec<ParserErr>) {
+        let shift = source.len() as isize - lines.len() as isize;
+        let replaced_end = lines.start + source.len();
+        // where a position of the previous source is in the new one, if it was not replaced
+        let moved = |line: usize| match line {
+            line if line < lines.start => Some(line),
+            line if line >= lines.end => Some(line.wrapping_add_signed(shift)),
+            _ => None,
+        };
+
+        let tokens: Vec<Vec<(Token, Span)>> = source.iter()
+            .enumerate()
+            .map(|(i, line)| Lexer::lex_line(line, lines.start + i))
+            .collect();
+        self.tokens.lines.splice(lines.clone(), tokens);
+        for (_, span) in self.tokens.lines[replaced_end..].iter_mut().flatten() {
+            span.shift(shift);
+        }
+        self.source.splice(lines.clone(), source);
+
+        let kept = self.statements.iter().take_while(|statement| statement.furthest.0 < lines.start).count();
+        let mut previous = self.statements.split_off(kept);
+        let nodes_start: usize = self.statements.iter().map(|statement| statement.nodes).sum();
+        let errs_start: usize = self.statements.iter().map(|statement| statement.errs).sum();
+
+        // the statement at the end of the source always looks past the replaced lines
+        self.tokens.cursor = previous[0].start;
+        self.restore(&previous[0].state);
+
+        let mut nodes = vec![];
+        let mut new_errs = vec![];
+        let (mut next, mut old_nodes, mut old_errs) = (0, nodes_start, errs_start);
+
+        // parses until a statement after the replaced lines starts where the parser is, in the same state
+        let resumed = loop {
+            // only statements after the replaced lines read the same tokens as before
+            while let Some(statement) = previous.get(next) {
+                let start = (statement.start.0.wrapping_add_signed(shift), statement.start.1);
+                if statement.start.0 >= lines.end && start >= self.tokens.cursor {
+                    break;
                 }
+
+                old_nodes += statement.nodes;
+                old_errs += statement.errs;
+                next += 1;
+            }
+
+            let resumable = previous.get(next).is_some_and(|statement| {
+                let start = (statement.start.0.wrapping_add_signed(shift), statement.start.1);
+                let state = moved(statement.state.prev_span.line).map(|line| ParseState {
+                    prev_span: Span { line, ..statement.state.prev_span },
+                    ..statement.state.clone()
+                });
+
+                start == self.tokens.cursor && state == Some(self.state())
+            });
+
+            if resumable {
+                break Some(next);
+            }
+
+            if !self.parse_statement(&mut nodes, &mut new_errs) {
+                break None;
+            }
+        };
+
+        let Some(next) = resumed else {
+            result.truncate(nodes_start);
+            result.extend(nodes);
+            errs.truncate(errs_start);
+            errs.extend(new_errs);
+            return;
+        };
+
+        for mut statement in previous.drain(next..) {
+            statement.start.0 = statement.start.0.wrapping_add_signed(shift);
+            statement.furthest.0 = statement.furthest.0.wrapping_add_signed(shift);
+            statement.state.prev_span.line = moved(statement.state.prev_span.line).unwrap_or_default();
+            self.statements.push(statement);
+        }
+
+        let moved_nodes = nodes_start + nodes.len();
+        result.splice(nodes_start..old_nodes, nodes);
+        result[moved_nodes..].iter_mut().for_each(|node| node.shift(shift));
+
+        let moved_errs = errs_start + new_errs.len();
+        errs.splice(errs_start..old_errs, new_errs);
+        errs[moved_errs..].iter_mut().for_each(|err| err.shift(shift));
+    }
+
+    /**
+     * Parses the next top level statement, returning false once there are no tokens left
+     **/
+    fn parse_statement(&mut self, result: &mut Vec<AstNode>, errs: &mut Vec<ParserErr>) -> bool {
+        let start = self.tokens.cursor;
+        let state = self.state();
+        let (nodes, errs_before) = (result.len(), errs.len());
+        self.tokens.furthest.set(start);
+
+        // skip all starting empty tokens
+        self.skip_empty();
+
+        let node = self.parse_node();
+        for err in self.recovered.drain(..) {
+            err.print(&self.source);
+            errs.push(err);
+        }
+
+        let parsed = match node {
+            Ok(Some(node)) => {
+                result.push(node);
+                true
             }
+            Ok(None) => false,
+            Err(err) => {
+                err.print(&self.source);
+                errs.push(err);
+                true
+            }
+        };
+
+        self.statements.push(Statement {
+            start,
+            furthest: self.tokens.furthest.get(),
+            state,
+            nodes: result.len() - nodes,
+            errs: errs.len() - errs_before,
+        });
+
+        parsed
+    }
+
+    fn state(&self) -> ParseState {
+        ParseState {
+            prev_span: self.prev_span,
+            parsing_ws_delim: self.parsing_ws_delim,
+            ws_delim_in_parenth: self.ws_delim_in_parenth,
         }
+    }
 
-        return (result, errs);
+    fn restore(&mut self, state: &ParseState) {
+        self.prev_span = state.prev_span;
+        self.parsing_ws_delim = state.parsing_ws_delim;
+        self.ws_delim_in_parenth = state.ws_delim_in_parenth;
     }
 
     fn parse_node(&mut self) -> Result<Option<AstNode>, ParserErr> {
-        if let None = self.peek() {
+        if self.peek().is_none() {
             return Ok(None);
         }
 
         if peek_match_ignore_ws!(self, 0, Token::Return) {
             self.skip_empty();
             self.next();
+            let span = self.prev_span;
             let expr = self.parse_expr()?;
-            return Ok(Some(AstNode::Return(expr)));
+            return Ok(Some(AstNode::Return(expr, span)));
+        }
+
+        if peek_match_ignore_ws!(self, 0, Token::Type) {
+            self.skip_empty();
+            self.next();
+            let name = expect!(self, true, Token::Ident(name) => Ok(name))?;
+            let span = self.prev_span;
+            let mut params = vec![];
+
+            if let Some((Token::ParenthOpen, _)) = self.peek() {
+                self.next();
+                params.push(expect!(self, true, Token::Ident(param) => Ok(param))?);
+
+                while peek_match_ignore_ws!(self, 0, Token::Comma) {
+                    self.skip_empty();
+                    self.next();
+                    params.push(expect!(self, true, Token::Ident(param) => Ok(param))?);
+                }
+
+                expect!(self, true, Token::ParenthClose => Ok(()))?;
+            }
+
+            expect!(self, true, Token::Assignment => Ok(()))?;
+            let type_ = self.parse_type()?;
+            return Ok(Some(AstNode::TypeAlias(name, params, type_, span)));
+        }
+
+        // trait declaration, e.g. `trait Show { show: (Self) -> Str }`
+        if peek_match_ignore_ws!(self, 0, Token::Trait) {
+            self.skip_empty();
+            self.next();
+            let name = expect!(self, true, Token::Ident(name) => Ok(name))?;
+            let span = self.prev_span;
+            let mut methods = vec![];
+
+            expect!(self, true, Token::FuncOpen => Ok(()))?;
+
+            while !peek_match_ignore_ws!(self, 0, Token::FuncClose) {
+                methods.push(self.parse_simple_ident()?);
+            }
+
+            expect!(self, true, Token::FuncClose => Ok(()))?;
+            return Ok(Some(AstNode::Trait(name, methods, span)));
+        }
+
+        // trait implementation, e.g. `impl Show for I32 { show = |x| "int" }`
+        if peek_match_ignore_ws!(self, 0, Token::Impl) {
+            self.skip_empty();
+            self.next();
+            let name = expect!(self, true, Token::Ident(name) => Ok(name))?;
+            let span = self.prev_span;
+            expect!(self, true, Token::For => Ok(()))?;
+            let type_ = self.parse_type()?;
+            let methods = self.parse_function_body()?;
+            return Ok(Some(AstNode::Impl(name, type_, methods, span)));
         }
 
         let ident = self.parse_ident()?;
@@ -137,15 +423,41 @@ impl PropsParser {
     }
 
     fn parse_expr(&mut self) -> Result<Expression, ParserErr> {
-        let mut expr = if peek_match_ignore_ws!(self, 0, Token::StringLiteral(_)) {
-            expect!(self, true, Token::StringLiteral(str) => Ok(Expression::StrLiteral(str)))
+        let mut exprs = vec![self.parse_single_expr()?];
+
+        while peek_match_ignore_ws!(self, 0, Token::Comma) {
+            self.skip_empty();
+            self.next();
+            exprs.push(self.parse_single_expr()?);
+        }
+
+        if exprs.len() == 1 {
+            return Ok(exprs.pop().unwrap());
+        }
+
+        Ok(Expression::Compound(exprs))
+    }
+
+    fn parse_single_expr(&mut self) -> Result<Expression, ParserErr> {
+        if self.peek_parenth_compound() {
+            self.skip_empty();
+            self.next();
+            let expr = self.parse_expr()?;
+            expect!(self, true, Token::ParenthClose => Ok(()))?;
+            return Ok(expr);
+        }
+
+        if peek_match_ignore_ws!(self, 0, Token::StringLiteral(_), Token::CharLiteral(_)) {
+            expect!(self, true, Token::StringLiteral(str) => Ok(Expression::StrLiteral(str)), Token::CharLiteral(c) => Ok(Expression::CharLiteral(c)))
         } else if peek_match_ignore_ws!(self, 0, Token::Pipe, Token::FuncOpen) {
             let has_params = expect!(self, true, Token::Pipe => Ok(true), Token::FuncOpen => Ok(false))?;
+            let span = self.prev_span;
             let mut params = vec![];
 
             let statements = if has_params {
                 loop {
                     let id = expect!(self, true, Token::Ident(id) => Ok(id))?;
+                    let span = self.prev_span;
                     let type_ = if peek_match_ignore_ws!(self, 0, Token::TypeAnnotator) {
                         self.skip_empty();
                         self.next();
@@ -154,7 +466,7 @@ impl PropsParser {
                         Type::Undefined
                     };
 
-                    params.push((id, type_));
+                    params.push(Identifier::Identifier(Rc::new(id), Rc::new(type_), span));
 
                     if let Some((Token::Whitespace, _)) = self.peek() {
                         self.next();
@@ -170,9 +482,14 @@ impl PropsParser {
                 if peek_match_ignore_ws!(self, 0, Token::FuncOpen) {
                     self.parse_function_body()?
                 } else { 
-                    vec![AstNode::Return(self.parse_expr()?)]
+                    // an expression body returns from where it starts
+                    self.skip_empty();
+                    let span = self.peek().map(|(_, span)| *span).unwrap_or(self.prev_span);
+                    vec![AstNode::Return(self.parse_expr()?, span)]
                 }
             } else {
+                // the `{` was already consumed to tell it apart from a parameter list
+                self.tokens.unread();
                 self.parse_function_body()?
             };
 
@@ -180,19 +497,42 @@ impl PropsParser {
                 params,
                 statements,
                 return_type: Type::Undefined,
+                span,
+                end: self.prev_span,
             })
         } else {
             Ok(Expression::MathExpr(self.parse_math_expr()?))
-        }?;
+        }
+    }
 
-        while peek_match_ignore_ws!(self, 0, Token::Comma) {
-            self.skip_empty();
-            self.next();
-            let expr2 = self.parse_expr()?;
-            expr = expr.compound(expr2);
+    /**
+     * Returns true if the next significant token opens a parenthesis containing a top level comma, e.g. `(1, 2)`
+     **/
+    fn peek_parenth_compound(&self) -> bool {
+        let mut tokens = (0..)
+            .map_while(|i| self.tokens.get(i))
+            .map(|(t, _)| t)
+            .skip_while(|t| t.is_insignificant());
+
+        if tokens.next() != Some(&Token::ParenthOpen) {
+            return false;
+        }
+
+        let mut depth = 1;
+        for token in tokens {
+            match token {
+                Token::ParenthOpen => depth += 1,
+                Token::ParenthClose => depth -= 1,
+                Token::Comma if depth == 1 => return true,
+                _ => {}
+            }
+
+            if depth == 0 {
+                break;
+            }
         }
 
-        Ok(expr)
+        false
     }
     
     fn parse_function_body(&mut self) -> Result<Vec<AstNode>, ParserErr> {
@@ -201,10 +541,20 @@ impl PropsParser {
         expect!(self, true, Token::FuncOpen => Ok(()))?;
 
         while !peek_match_ignore_ws!(self, 0, Token::FuncClose) {
-            if let Some(ast) = self.parse_node()? {
-                result.push(ast);
-            } else {
-                break;
+            self.skip_empty();
+
+            match self.parse_node() {
+                Ok(Some(ast)) => result.push(ast),
+                // an unclosed body ends with the file, keep what was parsed of it
+                Ok(None) => {
+                    self.recovered.push(ParserErr::UnexpectedEof { span: self.prev_span });
+                    return Ok(result);
+                }
+                // a broken statement only loses itself rather than the whole function
+                Err(err) => {
+                    self.recovered.push(err);
+                    self.skip_line();
+                }
             }
         }
 
@@ -237,60 +587,166 @@ impl PropsParser {
     }
     
     fn parse_type(&mut self) -> Result<Type, ParserErr> {
+        let mut type_ = self.parse_simple_type()?;
+
+        // optional, e.g. `I32?`
+        while let Some((Token::Question, _)) = self.peek() {
+            self.next();
+            type_ = Type::Optional(Box::new(type_));
+        }
+
+        Ok(type_)
+    }
+
+    fn parse_simple_type(&mut self) -> Result<Type, ParserErr> {
+        // impure function signature, e.g. `impure (Str) -> Str`
+        if peek_match_ignore_ws!(self, 0, Token::Impure) {
+            self.skip_empty();
+            let (token, span) = self.next().unwrap();
+
+            return match self.parse_simple_type()? {
+                Type::Function(params, return_type, _) => Ok(Type::Function(params, return_type, Effect::Impure)),
+                _ => Err(ParserErr::UnexpectedToken {
+                    line: span.line + 1,
+                    pos: span.end - 1,
+                    token,
+                }),
+            };
+        }
+
         if peek_match_ignore_ws!(self, 0, Token::ParenthOpen) {
             self.skip_empty();
             self.next();
-            let mut types: Vec<Access<Type>> = vec![Access::Owned(expect!(self, true, Token::Ident(str) => Ok(str))?.into())];
-            
-            while peek_match_ignore_ws!(self, 0, Token::Comma) {
+            let types = self.parse_type_list()?;
+
+            // function signature, e.g. `(I32, Str) -> Str`
+            if peek_match_ignore_ws!(self, 0, Token::Arrow) {
                 self.skip_empty();
                 self.next();
-                types.push(Access::Owned(expect!(self, true, Token::Ident(str) => Ok(str))?.into()));
+                let return_type = self.parse_type()?;
+                return Ok(Type::Function(types, Box::new(return_type), Effect::Pure));
             }
-            
-            expect!(self, true, Token::ParenthClose => Ok(()))?;
+
             return Ok(Type::Compound(types));
         }
 
         let type_ = expect!(self, true, Token::Ident(str) => Ok(str))?;
+
+        // type arguments, e.g. `Pair(I32, Str)`
+        if let Some((Token::ParenthOpen, _)) = self.peek() {
+            self.next();
+            let args = self.parse_type_list()?;
+            return Ok(Type::Defined(type_, args));
+        }
+
         Ok(type_.into())
     }
 
+    /**
+     * Parses comma separated types up to and including the closing parenthesis
+     **/
+    fn parse_type_list(&mut self) -> Result<Vec<Access<Type>>, ParserErr> {
+        let mut types = vec![];
+
+        if !peek_match_ignore_ws!(self, 0, Token::ParenthClose) {
+            types.push(Access::Owned(self.parse_type()?));
+
+            while peek_match_ignore_ws!(self, 0, Token::Comma) {
+                self.skip_empty();
+                self.next();
+                types.push(Access::Owned(self.parse_type()?));
+            }
+        }
+
+        expect!(self, true, Token::ParenthClose => Ok(()))?;
+        Ok(types)
+    }
+
     pub fn parse_ident(&mut self) -> Result<Identifier, ParserErr> {
-        let mut ident = self.parse_simple_ident()?;
+        let mut idents = vec![self.parse_simple_ident()?];
 
         while peek_match_ignore_ws!(self, 0, Token::Comma) {
             self.skip_empty();
             self.next();
-            let rhs = self.parse_simple_ident()?;
-            ident = ident.compound(rhs);
+            idents.push(self.parse_simple_ident()?);
         }
 
-        Ok(ident)
+        if idents.len() == 1 {
+            return Ok(idents.pop().unwrap());
+        }
+
+        Ok(Identifier::Compound(idents))
     }
 
     fn parse_simple_ident(&mut self) -> Result<Identifier, ParserErr> {
+        if peek_match_ignore_ws!(self, 0, Token::Mut) {
+            self.skip_empty();
+            self.next();
+            return Ok(Identifier::Mutable(Box::new(self.parse_simple_ident()?)));
+        }
+
+        // nested compound, e.g. the `(a, b)` in `(a, b), c = ...`
+        if peek_match_ignore_ws!(self, 0, Token::ParenthOpen) {
+            self.skip_empty();
+            self.next();
+            let ident = self.parse_ident()?;
+            expect!(self, true, Token::ParenthClose => Ok(()))?;
+            return Ok(ident);
+        }
+
         let str = expect!(self, true, Token::Ident(str) => Ok(str))?;
+        let span = self.prev_span;
 
         if peek_match_ignore_ws!(self, 0, Token::TypeAnnotator) {
             self.skip_empty();
             self.next();
             let type_ = self.parse_type()?;
-            return Ok(Identifier::Identifier(Rc::new(str), Rc::new(type_)));
+            return Ok(Identifier::Identifier(Rc::new(str), Rc::new(type_), span));
         }
 
-        let mut ident = Identifier::Identifier(Rc::new(str), Rc::new(Type::Undefined));
-        while peek_match_ignore_ws!(self, 0, Token::Period) {
-            self.skip_empty();
-            self.next();
-            let rhs = expect!(self, true, Token::Ident(str) => Ok(Identifier::Identifier(Rc::new(str), Rc::new(Type::Undefined))))?;
-            ident = Identifier::Accessor(Box::new(ident), Box::new(rhs));
+        let mut ident = Identifier::Identifier(Rc::new(str), Rc::new(Type::Undefined), span);
+        while peek_match_ignore_ws!(self, 0, Token::Period, Token::SafeAccess) {
+            let safe = expect!(self, true, Token::Period => Ok(false), Token::SafeAccess => Ok(true))?;
+
+            // keep the receiver of an unfinished access like `x.` so it is still typed
+            let Some((Token::Ident(_), _)) = self.peek() else {
+                let err = self.unexpected_peek();
+                self.recovered.push(err);
+                break;
+            };
+
+            let rhs = expect!(self, false, Token::Ident(str) => Ok(Identifier::Identifier(Rc::new(str), Rc::new(Type::Undefined), self.prev_span)))?;
+
+            ident = if safe {
+                Identifier::SafeAccessor(Box::new(ident), Box::new(rhs))
+            } else {
+                Identifier::Accessor(Box::new(ident), Box::new(rhs))
+            };
         }
 
         Ok(ident)
     }
 
     pub fn parse_math_expr(&mut self) -> Result<MathExpr, ParserErr> {
+        let mut left = self.parse_additive_expr()?;
+
+        while peek_match_ignore_ws!(self, 0, Token::Coalesce) {
+            self.skip_empty();
+            self.next();
+            // the fallback is commonly a string, which is not a math expression
+            let right = if peek_match_ignore_ws!(self, 0, Token::StringLiteral(_), Token::CharLiteral(_)) {
+                expect!(self, true, Token::StringLiteral(str) => Ok(Expression::StrLiteral(str)), Token::CharLiteral(c) => Ok(Expression::CharLiteral(c)))?
+            } else {
+                Expression::MathExpr(self.parse_additive_expr()?)
+            };
+
+            left = MathExpr::Coalesce(Box::new(left), Box::new(right));
+        }
+
+        Ok(left)
+    }
+
+    fn parse_additive_expr(&mut self) -> Result<MathExpr, ParserErr> {
         let mut left = self.parse_multiplicative_expr()?;
 
         while peek_match_ignore_ws!(self, 0, Token::Addition, Token::Subtraction) {
@@ -307,7 +763,7 @@ impl PropsParser {
     }
 
     fn parse_multiplicative_expr(&mut self) -> Result<MathExpr, ParserErr> {
-        let mut left = self.parse_parenth_expr()?;
+        let mut left = self.parse_operand(true)?;
 
         while peek_match_ignore_ws!(self, 0, Token::Multiplication, Token::Division, Token::Power, Token::Mod) {
             let operation = expect! {
@@ -319,7 +775,7 @@ impl PropsParser {
                 Token::Mod => Ok(MathOp::Mod)
             }?;
 
-            let right = self.parse_parenth_expr()?;
+            let right = self.parse_operand(true)?;
 
             left = MathExpr::BinaryOp(
                 Box::new(left),
@@ -331,7 +787,11 @@ impl PropsParser {
         Ok(left)
     }
 
-    fn parse_parenth_expr(&mut self) -> Result<MathExpr, ParserErr> {
+    /**
+     * Parses a parenthesized expression or a unary operand with the postfixes following it. A negated operand
+     * leaves its cast to the negation, so `-1 as U8` casts -1 like it does in Rust
+     **/
+    fn parse_operand(&mut self, casts: bool) -> Result<MathExpr, ParserErr> {
         if peek_match_ignore_ws!(self, 0, Token::ParenthOpen) {
             self.skip_empty();
             self.next();
@@ -339,27 +799,51 @@ impl PropsParser {
             let result = self.parse_math_expr()?;
             self.ws_delim_in_parenth = false;
             expect!(self, true, Token::ParenthClose => Ok(()))?;
-            return Ok(result);
+            return self.parse_postfix(result, casts);
         }
 
-        self.parse_unary_expr()
+        let expr = self.parse_unary_operand()?;
+        self.parse_postfix(expr, casts)
     }
 
-    fn parse_unary_expr(&mut self) -> Result<MathExpr, ParserErr> {
+    /**
+     * Parses casts and error propagation following an expression, e.g. `x as I64` or `(parse s)?`
+     **/
+    fn parse_postfix(&mut self, mut expr: MathExpr, casts: bool) -> Result<MathExpr, ParserErr> {
+        loop {
+            if let Some((Token::Question, span)) = self.peek() {
+                let span = *span;
+                self.next();
+                expr = MathExpr::Try(Box::new(expr), span);
+            } else if casts && peek_match_ignore_ws!(self, 0, Token::As) {
+                self.skip_empty();
+                let start = self.next().unwrap().1;
+                let type_ = self.parse_type()?;
+                let span = Span::new(start.line, start.start, self.prev_span.end);
+                expr = MathExpr::Cast(Box::new(expr), type_, span);
+            } else {
+                return Ok(expr);
+            }
+        }
+    }
+
+    fn parse_unary_operand(&mut self) -> Result<MathExpr, ParserErr> {
         if peek_match_ignore_ws!(self, 0, Token::Subtraction) {
             self.skip_empty();
             self.next();
-            Ok(MathExpr::Negate(Box::new(self.parse_parenth_expr()?)))
+            Ok(MathExpr::Negate(Box::new(self.parse_operand(false)?)))
         } else {
             if peek_match_ignore_ws!(self, 0, Token::Number(_)) { 
-                expect!(self, true, Token::Number(num) => Ok(MathExpr::Literal(num)))
+                expect!(self, true, Token::Number(num) => Ok(MathExpr::Literal(num, self.prev_span)))
+            } else if peek_match_ignore_ws!(self, 0, Token::None) {
+                expect!(self, true, Token::None => Ok(MathExpr::None(self.prev_span)))
             } else if peek_match_ignore_ws!(self, 0, Token::Ident(_)) {
-                let ident = self.parse_ident()?;
+                let ident = self.parse_simple_ident()?;
                 
                 if self.parsing_ws_delim && !self.ws_delim_in_parenth {  
                     Ok(MathExpr::Identifier(ident))
                 } else if let Some((Token::Whitespace, _)) = self.peek() { 
-                    if peek_match_ignore_ws!(self, 1, Token::Addition, Token::Subtraction, Token::Multiplication, Token::Division, Token::Mod, Token::Power) {
+                    if peek_match_ignore_ws!(self, 1, Token::Addition, Token::Subtraction, Token::Multiplication, Token::Division, Token::Mod, Token::Power, Token::As, Token::Coalesce) {
                         self.next();
                         Ok(MathExpr::Identifier(ident))
                     } else { 
@@ -375,6 +859,19 @@ impl PropsParser {
         }
     }
 
+    /**
+     * Skips the rest of the current line, stopping before a `}` which may close the enclosing body
+     **/
+    fn skip_line(&mut self) {
+        while let Some((token, _)) = self.peek() {
+            if matches!(token, Token::Newline | Token::FuncClose) {
+                break;
+            }
+
+            self.next();
+        }
+    }
+
     fn skip_empty(&mut self) {
         while let Some(tok) = self.peek() {
             if !tok.0.is_insignificant() {
@@ -385,33 +882,50 @@ impl PropsParser {
         }
     }
 
-    fn next(&mut self) -> Option<(Token, usize)> {
+    fn next(&mut self) -> Option<(Token, Span)> {
         match self.tokens.pop_front() {
             None => None,
             Some(t) => {
-                if let Token::Newline = t.0 {
-                    self.line += 1;
-                }
+                self.prev_span = t.1;
 
                 Some(t)
             }
         }
     }
 
-    fn peek(&mut self) -> Option<&(Token, usize)> {
-        match self.tokens.get(0) {
-            None => None,
-            Some(t) => Some(t)
-        }
+    fn peek(&mut self) -> Option<&(Token, Span)> {
+        self.tokens.front()
     }
     
+    /**
+     * An error for the next token without consuming it
+     **/
+    fn unexpected_peek(&mut self) -> ParserErr {
+        match self.peek() {
+            Some((token, span)) => ParserErr::UnexpectedToken {
+                line: span.line + 1,
+                pos: span.end - 1,
+                token: token.clone(),
+            },
+            None => ParserErr::UnexpectedEof { span: self.prev_span },
+        }
+    }
+
     fn unexpected_token(&mut self) -> ParserErr {
-        let token = self.next().unwrap();
-        
+        let Some(token) = self.next() else {
+            return ParserErr::UnexpectedEof { span: self.prev_span };
+        };
+
         ParserErr::UnexpectedToken {
-            line: self.line,
-            pos: token.1,
+            line: token.1.line + 1,
+            pos: token.1.end - 1,
             token: token.0,
         }
     }
 }
+
+impl Default for PropsParser {
+    fn default() -> Self {
+        PropsParser::new()
+    }
+}
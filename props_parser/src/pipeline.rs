@@ -1,14 +1,17 @@
-use thiserror::__private::AsDisplay;
+use std::ops::Range;
 use crate::types::typer::PropsTyper;
 use crate::error::ParserErr;
 use crate::nodes::AstNode;
 use crate::parser::PropsParser;
+use crate::symbols::SymbolTable;
 use crate::types::TypeEnvironment;
 
 pub struct PropsPipeline {
     parser: PropsParser,
     type_environment: TypeEnvironment,
     typer: PropsTyper,
+    // how many of the errors of the last parse were found parsing rather than typing
+    parse_errs: usize,
 }
 
 impl PropsPipeline {
@@ -19,21 +22,50 @@ impl PropsPipeline {
             parser,
             typer: PropsTyper,
             type_environment: type_env,
+            parse_errs: 0,
         }
     }
     
     pub fn parse(&mut self, source: String) -> (Vec<AstNode>, Vec<ParserErr>) {
         self.parser.init(source);
-        self.type_environment.clear();
         
-        let (ast, _) = self.parser.parse();
+        let (ast, mut errs) = self.parser.parse();
+        self.parse_errs = errs.len();
+        self.type_check(&ast, &mut errs);
+        
+        (ast, errs)
+    }
+
+    /**
+     * Replaces a range of lines of the last parsed source, updating the nodes and errors parsing it returned.
+     * Only the statements around the replaced lines are parsed again, but the whole source is typed again
+     **/
+    pub fn reparse(&mut self, lines: Range<usize>, source: Vec<String>, ast: &mut Vec<AstNode>, errs: &mut Vec<ParserErr>) {
+        errs.truncate(self.parse_errs);
+        self.parser.reparse(lines, source, ast, errs);
+        self.parse_errs = errs.len();
+        self.type_check(ast, errs);
+    }
+
+    fn type_check(&mut self, ast: &[AstNode], errs: &mut Vec<ParserErr>) {
+        self.type_environment.clear();
+
         let mut type_errs = vec![];
-        self.typer.process(&ast, &mut self.type_environment, &mut type_errs);
-        for err in type_errs.iter() {
+        self.typer.process(ast, &mut self.type_environment, &mut type_errs);
+        for err in type_errs.iter_mut() {
+            err.attach_source(self.parser.source());
             eprintln!("{}", err);
         }
-        
-        (ast, type_errs)
+
+        self.type_environment.symbols_mut().attach_docs(self.parser.source());
+        errs.extend(type_errs);
+    }
+
+    /**
+     * The symbols of the last parsed source
+     **/
+    pub fn symbols(&self) -> &SymbolTable {
+        self.type_environment.symbols()
     }
 }
 
@@ -0,0 +1,231 @@
+use std::collections::HashMap;
+use crate::nodes::Span;
+use crate::types::Type;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolKind {
+    Variable,
+    Parameter,
+    Method,
+    Type,
+    Trait,
+}
+
+/**
+ * A name bound by an assignment, a function parameter, a method of an impl or a type declaration,
+ * with what the typer learned about it
+ **/
+#[derive(Debug, Clone)]
+pub struct Symbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    // where the name is first bound
+    pub span: Span,
+    // the type of the bound value, which may be narrower than the annotation
+    pub type_: Type,
+    pub annotation: Option<Type>,
+    pub mutable: bool,
+    pub doc: Option<String>,
+    // the function body it is declared in, none at the top level
+    pub scope: Option<usize>,
+    // the body of the function literal bound to it, whose parameters name its arguments
+    pub body: Option<usize>,
+}
+
+impl Symbol {
+    pub(crate) fn new(name: String, kind: SymbolKind, span: Span, type_: Type) -> Self {
+        Symbol {
+            name,
+            kind,
+            span,
+            type_,
+            annotation: None,
+            mutable: false,
+            doc: None,
+            scope: None,
+            body: None,
+        }
+    }
+}
+
+/**
+ * Every symbol of a program with the places they are used, and the type of each typed expression
+ **/
+#[derive(Debug, Default)]
+pub struct SymbolTable {
+    symbols: Vec<Symbol>,
+    references: Vec<(Span, usize)>,
+    types: Vec<(Span, Type)>,
+    // the first and last token of each function body
+    scopes: Vec<(Span, Span)>,
+    open_scopes: Vec<usize>,
+}
+
+impl SymbolTable {
+    pub fn clear(&mut self) {
+        self.symbols.clear();
+        self.references.clear();
+        self.types.clear();
+        self.scopes.clear();
+        self.open_scopes.clear();
+    }
+
+    pub fn symbols(&self) -> &[Symbol] {
+        &self.symbols
+    }
+
+    pub fn symbol(&self, id: usize) -> &Symbol {
+        &self.symbols[id]
+    }
+
+    /**
+     * Records a symbol declared in the innermost open scope
+     **/
+    pub(crate) fn declare(&mut self, symbol: Symbol) -> usize {
+        self.symbols.push(Symbol { scope: self.open_scopes.last().copied(), ..symbol });
+        self.symbols.len() - 1
+    }
+
+    pub(crate) fn enter_scope(&mut self, start: Span, end: Span) {
+        self.scopes.push((start, end));
+        self.open_scopes.push(self.scopes.len() - 1);
+    }
+
+    pub(crate) fn exit_scope(&mut self) {
+        self.open_scopes.pop();
+    }
+
+    /**
+     * Links a symbol to the function literal whose body starts at a span
+     **/
+    pub(crate) fn bind_body(&mut self, id: usize, start: Span) {
+        self.symbols[id].body = self.scopes.iter().position(|(scope_start, _)| *scope_start == start);
+    }
+
+    pub(crate) fn symbol_mut(&mut self, id: usize) -> &mut Symbol {
+        &mut self.symbols[id]
+    }
+
+    pub(crate) fn reference(&mut self, span: Span, id: usize) {
+        self.references.push((span, id));
+    }
+
+    pub(crate) fn record_type(&mut self, span: Span, type_: Type) {
+        self.types.push((span, type_));
+    }
+
+    /**
+     * The symbol declared or used at a position
+     **/
+    pub fn symbol_at(&self, line: usize, pos: usize) -> Option<usize> {
+        self.symbols.iter()
+            .position(|symbol| symbol.span.contains(line, pos))
+            .or_else(|| self.references.iter().find(|(span, _)| span.contains(line, pos)).map(|(_, id)| *id))
+    }
+
+    /**
+     * The symbol declared or used at each span, for looking up every name of a source at once.
+     * A declaration takes precedence over a use at the same span, like in `symbol_at`
+     **/
+    pub fn span_index(&self) -> HashMap<Span, usize> {
+        let mut index = HashMap::new();
+        for (id, symbol) in self.symbols.iter().enumerate() {
+            index.entry(symbol.span).or_insert(id);
+        }
+
+        for (span, id) in self.references.iter() {
+            index.entry(*span).or_insert(*id);
+        }
+
+        index
+    }
+
+    /**
+     * The symbols which can be used by name at a position, a later declaration shadowing an earlier one
+     **/
+    pub fn visible_at(&self, line: usize, pos: usize) -> Vec<&Symbol> {
+        let mut visible: Vec<&Symbol> = vec![];
+
+        for symbol in self.symbols.iter() {
+            let declared_before = (symbol.span.line, symbol.span.end) <= (line, pos);
+            let in_scope = symbol.scope.is_none_or(|scope| {
+                let (start, end) = self.scopes[scope];
+                (start.line, start.start) <= (line, pos) && (line, pos) <= (end.line, end.end)
+            });
+
+            if symbol.kind == SymbolKind::Method || !declared_before || !in_scope {
+                continue;
+            }
+
+            visible.retain(|other| other.name != symbol.name);
+            visible.push(symbol);
+        }
+
+        visible
+    }
+
+    /**
+     * Every place a symbol is used, not including where it is declared
+     **/
+    pub fn references(&self, id: usize) -> impl Iterator<Item = Span> + '_ {
+        self.references.iter()
+            .filter(move |(_, symbol)| *symbol == id)
+            .map(|(span, _)| *span)
+    }
+
+    /**
+     * The parameters of the function literal bound to a symbol, none if it is not bound to one
+     **/
+    pub fn parameters(&self, id: usize) -> Option<Vec<&Symbol>> {
+        let body = self.symbols[id].body?;
+
+        Some(self.symbols.iter()
+            .filter(|symbol| symbol.kind == SymbolKind::Parameter && symbol.scope == Some(body))
+            .collect())
+    }
+
+    /**
+     * The bindings of function bodies which are never used, top level bindings being the values of the program
+     **/
+    pub fn unused(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.symbols.len()).filter(|id| {
+            let symbol = &self.symbols[*id];
+            symbol.kind == SymbolKind::Variable && symbol.scope.is_some() && !symbol.name.starts_with('_') && self.references(*id).next().is_none()
+        })
+    }
+
+    /**
+     * The type of the innermost expression at a position
+     **/
+    pub fn type_at(&self, line: usize, pos: usize) -> Option<(Span, &Type)> {
+        self.types.iter()
+            .filter(|(span, _)| span.contains(line, pos))
+            .min_by_key(|(span, _)| span.len())
+            .map(|(span, type_)| (*span, type_))
+    }
+
+    /**
+     * Attaches the `///` comment lines directly above a statement to the first symbol it declares
+     **/
+    pub fn attach_docs(&mut self, lines: &[String]) {
+        let mut documented_lines = vec![];
+
+        for symbol in self.symbols.iter_mut() {
+            if symbol.kind == SymbolKind::Parameter || documented_lines.contains(&symbol.span.line) {
+                continue;
+            }
+
+            documented_lines.push(symbol.span.line);
+
+            let doc: Vec<&str> = lines[..symbol.span.line.min(lines.len())].iter()
+                .rev()
+                .map_while(|line| line.trim_start().strip_prefix("///"))
+                .collect();
+
+            if !doc.is_empty() {
+                let doc: Vec<&str> = doc.iter().rev().map(|line| line.strip_prefix(' ').unwrap_or(line)).collect();
+                symbol.doc = Some(doc.join("\n"));
+            }
+        }
+    }
+}
@@ -4,17 +4,31 @@ use crate::types::Number;
 pub enum Token {
     Ident(String),
     StringLiteral(String),
+    CharLiteral(char),
     Unknown(String),
     Number(Number),
+    Comment,
 
     Pipe,
     TypeAnnotator,
     Comma,
     Period,
+    Question,
+    SafeAccess,
+    Coalesce,
     FuncOpen,
     FuncClose,
     Assignment,
+    Arrow,
     Return,
+    As,
+    Type,
+    Trait,
+    Impl,
+    For,
+    None,
+    Mut,
+    Impure,
 
     Addition,
     Subtraction,
@@ -40,26 +54,32 @@ pub enum Token {
 
 impl Token {
     pub fn is_insignificant(&self) -> bool {
-        match self {
-            Token::Whitespace => true,
-            Token::Newline => true,
-            Token::Indent(_) => true,
-            _ => false
-        }
+        matches!(self, Token::Whitespace | Token::Newline | Token::Indent(_))
     }
 
     pub fn is_ident(&self) -> bool {
-        match self {
-            Token::Ident(_) => true,
-            _ => false,
-        }
+        matches!(self, Token::Ident(_))
     }
     
-    pub fn len(&self) -> usize {
+    pub(crate) fn len(&self) -> usize {
         match self {
             Token::Ident(id) => id.len(),
             Token::Unknown(i) => i.len(),
             Token::Number(num) => num.len(),
+            Token::CharLiteral('\n' | '\t' | '\r' | '\0' | '\\' | '\'') => 4,
+            Token::CharLiteral(c) => c.len_utf8() + 2,
+            Token::Return => 6,
+            Token::Arrow => 2,
+            Token::As => 2,
+            Token::Type => 4,
+            Token::Trait => 5,
+            Token::Impl => 4,
+            Token::For => 3,
+            Token::Mut => 3,
+            Token::Impure => 6,
+            Token::None => 4,
+            Token::SafeAccess => 2,
+            Token::Coalesce => 2,
             Token::Equality => 2,
             Token::GreaterEqual => 2,
             Token::LessEqual => 2,
@@ -1,32 +1,61 @@
-use std::cmp::Ordering;
 use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::rc::Rc;
 use std::str::FromStr;
 use lazy_static::lazy_static;
-use strum::{Display, EnumIter};
+use num_bigint::BigInt;
+use num_traits::{FromPrimitive, ToPrimitive, Zero};
+use rust_decimal::Decimal;
+use strum::EnumIter;
 use strum::IntoEnumIterator;
-use crate::nodes::{Expression, Identifier, MathExpr};
+use crate::nodes::{MathOp, Span};
+use crate::symbols::{Symbol, SymbolKind, SymbolTable};
 use crate::util::Access;
 
 pub mod typer;
 
+// the largest power `exact_op` computes, a power of a BigInt grows without bound
+const MAX_POW_BITS: u64 = 1 << 20;
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Number {
     U8(u8),
     U16(u16),
     U32(u32),
     U64(u64),
+    U128(u128),
     I8(i8),
     I16(i16),
     I32(i32),
     I64(i64),
+    I128(i128),
     F32(f32),
     F64(f64),
+    BigInt(BigInt),
+    Decimal(Decimal),
 }
 
 impl Number {
+    /**
+     * Parses a literal with a type suffix, `n` for BigInt and `d` for Decimal
+     **/
+    pub fn parse_suffixed(s: &str, suffix: char) -> Result<Number, &'static str> {
+        match suffix {
+            'n' => BigInt::from_str(s)
+                .map(Number::BigInt)
+                .map_err(|_| "Failed to parse the string into a BigInt."),
+            // exact so digits beyond the precision of a decimal are an error rather than rounded
+            'd' => Decimal::from_str_exact(s)
+                .map(Number::Decimal)
+                .map_err(|_| "Failed to parse the string into a Decimal."),
+            _ => Err("Unknown number suffix."),
+        }
+    }
+
     pub fn parse_number(s: &str, has_decimal: bool) -> Result<Number, &'static str> {
         if has_decimal {
-            if let Ok(value) = s.parse::<f32>() {
+            // literals too large for an f32 parse to infinity rather than failing
+            if let Some(value) = s.parse::<f32>().ok().filter(|value| value.is_finite()) {
                 Ok(Number::F32(value))
             } else if let Ok(value) = s.parse::<f64>() {
                 Ok(Number::F64(value))
@@ -42,6 +71,8 @@ impl Number {
                 Ok(Number::U32(value))
             } else if let Ok(value) = s.parse::<u64>() {
                 Ok(Number::U64(value))
+            } else if let Ok(value) = s.parse::<u128>() {
+                Ok(Number::U128(value))
             } else if let Ok(value) = s.parse::<i8>() {
                 Ok(Number::I8(value))
             } else if let Ok(value) = s.parse::<i16>() {
@@ -50,24 +81,204 @@ impl Number {
                 Ok(Number::I32(value))
             } else if let Ok(value) = s.parse::<i64>() {
                 Ok(Number::I64(value))
+            } else if let Ok(value) = s.parse::<i128>() {
+                Ok(Number::I128(value))
             } else {
                 Err("Failed to parse the string into a valid number type.")
             }
         }
     }
 
+    #[allow(clippy::len_without_is_empty)]
     pub fn len(&self) -> usize {
         match self {
             Number::U8(num) => num.to_string().len(),
             Number::U16(num) => num.to_string().len(),
             Number::U32(num) => num.to_string().len(),
             Number::U64(num) => num.to_string().len(),
+            Number::U128(num) => num.to_string().len(),
             Number::I8(num) => num.to_string().len(),
             Number::I16(num) => num.to_string().len(),
             Number::I32(num) => num.to_string().len(),
             Number::I64(num) => num.to_string().len(),
+            Number::I128(num) => num.to_string().len(),
             Number::F32(num) => num.to_string().len(),
             Number::F64(num) => num.to_string().len(),
+            // including the suffix
+            Number::BigInt(num) => num.to_string().len() + 1,
+            Number::Decimal(num) => num.to_string().len() + 1,
+        }
+    }
+
+    /**
+     * The value of an integer literal, `None` for floating point and decimal literals and
+     * integers beyond i128
+     **/
+    pub fn as_int(&self) -> Option<i128> {
+        match self {
+            Number::U8(num) => Some(*num as i128),
+            Number::U16(num) => Some(*num as i128),
+            Number::U32(num) => Some(*num as i128),
+            Number::U64(num) => Some(*num as i128),
+            Number::U128(num) => i128::try_from(*num).ok(),
+            Number::I8(num) => Some(*num as i128),
+            Number::I16(num) => Some(*num as i128),
+            Number::I32(num) => Some(*num as i128),
+            Number::I64(num) => Some(*num as i128),
+            Number::I128(num) => Some(*num),
+            Number::BigInt(num) => num.to_i128(),
+            Number::F32(_) | Number::F64(_) | Number::Decimal(_) => None,
+        }
+    }
+
+    /**
+     * The value of a non negative integer literal, `None` for negative values and floating point literals
+     **/
+    pub fn as_uint(&self) -> Option<u128> {
+        match self {
+            Number::U128(num) => Some(*num),
+            Number::BigInt(num) => num.to_u128(),
+            _ => self.as_int().and_then(|num| u128::try_from(num).ok()),
+        }
+    }
+
+    pub fn as_float(&self) -> f64 {
+        match self {
+            Number::F32(num) => *num as f64,
+            Number::F64(num) => *num,
+            Number::U128(num) => *num as f64,
+            Number::BigInt(num) => num.to_f64().unwrap_or(f64::NAN),
+            Number::Decimal(num) => num.to_f64().unwrap_or(f64::NAN),
+            _ => self.as_int().unwrap() as f64,
+        }
+    }
+
+    /**
+     * Converts this number to another primitive type with the same semantics as rust's `as`,
+     * integers wrap around when narrowed, floats saturate at the bounds of an integer type
+     * (NaN becomes 0) and floats are rounded to the nearest representable value.
+     * Decimals are truncated toward zero and wrap like integers. Returns `None` if the value
+     * can not be represented, e.g. a float which is not finite as a BigInt
+     **/
+    pub fn cast(&self, to: &PrimitiveType) -> Option<Number> {
+        match to {
+            PrimitiveType::BigInt => return self.to_bigint().map(Number::BigInt),
+            PrimitiveType::Decimal => return self.to_decimal().map(Number::Decimal),
+            _ => {}
+        }
+
+        macro_rules! cast {
+            ($value:expr) => {
+                match to {
+                    PrimitiveType::U8 => Number::U8($value as u8),
+                    PrimitiveType::U16 => Number::U16($value as u16),
+                    PrimitiveType::U32 => Number::U32($value as u32),
+                    PrimitiveType::U64 => Number::U64($value as u64),
+                    PrimitiveType::U128 => Number::U128($value as u128),
+                    PrimitiveType::I8 => Number::I8($value as i8),
+                    PrimitiveType::I16 => Number::I16($value as i16),
+                    PrimitiveType::I32 => Number::I32($value as i32),
+                    PrimitiveType::I64 => Number::I64($value as i64),
+                    PrimitiveType::I128 => Number::I128($value as i128),
+                    PrimitiveType::F32 => Number::F32($value as f32),
+                    PrimitiveType::F64 => Number::F64($value as f64),
+                    _ => return None,
+                }
+            };
+        }
+
+        Some(match self {
+            // may not fit in an i128
+            Number::U128(value) => cast!(*value),
+            Number::BigInt(value) => cast!(Number::wrap_bigint(value)),
+            Number::Decimal(value) if to.int_range().is_some() => cast!(value.trunc().to_i128().unwrap()),
+            Number::F32(_) | Number::F64(_) | Number::Decimal(_) => cast!(self.as_float()),
+            _ => cast!(self.as_int().unwrap()),
+        })
+    }
+
+    // the lowest 128 bits in two's complement, which is what narrowing wraps to
+    fn wrap_bigint(value: &BigInt) -> u128 {
+        let modulus = BigInt::from(1u8) << 128;
+        let wrapped: BigInt = ((value % &modulus) + &modulus) % &modulus;
+        wrapped.to_u128().unwrap()
+    }
+
+    fn to_bigint(&self) -> Option<BigInt> {
+        match self {
+            Number::BigInt(value) => Some(value.clone()),
+            Number::U128(value) => Some(BigInt::from(*value)),
+            Number::Decimal(value) => value.trunc().to_i128().map(BigInt::from),
+            Number::F32(_) | Number::F64(_) => BigInt::from_f64(self.as_float().trunc()),
+            _ => self.as_int().map(BigInt::from),
+        }
+    }
+
+    fn to_decimal(&self) -> Option<Decimal> {
+        match self {
+            Number::Decimal(value) => Some(*value),
+            Number::F32(_) | Number::F64(_) => Decimal::from_f64(self.as_float()),
+            _ => self.to_bigint()?.to_i128().and_then(|value| Decimal::try_from_i128_with_scale(value, 0).ok()),
+        }
+    }
+
+    /**
+     * Applies an operator to two numbers of the same exact type, BigInt or Decimal, without overflow
+     * or binary floating point rounding. Returns `None` for other types, division by zero, a negative
+     * or fractional BigInt exponent, a BigInt power of more than `MAX_POW_BITS` bits, or a result beyond
+     * the range of a Decimal
+     **/
+    pub fn exact_op(&self, op: &MathOp, rhs: &Number) -> Option<Number> {
+        match (self, rhs) {
+            (Number::BigInt(lhs), Number::BigInt(rhs)) => Some(Number::BigInt(match op {
+                MathOp::Add => lhs + rhs,
+                MathOp::Sub => lhs - rhs,
+                MathOp::Mul => lhs * rhs,
+                MathOp::Div if rhs.is_zero() => return None,
+                MathOp::Div => lhs / rhs,
+                MathOp::Mod if rhs.is_zero() => return None,
+                MathOp::Mod => lhs % rhs,
+                MathOp::Pow => {
+                    let exponent = rhs.to_u32()?;
+                    // 0, 1 and -1 stay small whatever the exponent
+                    if lhs.bits() > 1 && (lhs.bits() - 1).saturating_mul(exponent as u64) > MAX_POW_BITS {
+                        return None;
+                    }
+
+                    lhs.pow(exponent)
+                }
+            })),
+            (Number::Decimal(lhs), Number::Decimal(rhs)) => Some(Number::Decimal(match op {
+                MathOp::Add => lhs.checked_add(*rhs)?,
+                MathOp::Sub => lhs.checked_sub(*rhs)?,
+                MathOp::Mul => lhs.checked_mul(*rhs)?,
+                MathOp::Div => lhs.checked_div(*rhs)?,
+                MathOp::Mod => lhs.checked_rem(*rhs)?,
+                MathOp::Pow => {
+                    // only whole exponents have an exact result
+                    if !rhs.fract().is_zero() {
+                        return None;
+                    }
+
+                    // by squaring, a large exponent overflows in a few steps rather than after as many multiplications
+                    let mut exponent = rhs.abs().to_u128()?;
+                    let mut base = *lhs;
+                    let mut result = Decimal::ONE;
+                    while exponent > 0 {
+                        if exponent & 1 == 1 {
+                            result = result.checked_mul(base)?;
+                        }
+
+                        exponent >>= 1;
+                        if exponent > 0 {
+                            base = base.checked_mul(base)?;
+                        }
+                    }
+
+                    if rhs.is_sign_negative() { Decimal::ONE.checked_div(result)? } else { result }
+                }
+            })),
+            _ => None,
         }
     }
 
@@ -78,16 +289,33 @@ impl Number {
             Number::U16(_) => PrimitiveType::I32,
             Number::U32(_) => PrimitiveType::I64,
             Number::U64(_) => PrimitiveType::U64,
+            Number::U128(_) => PrimitiveType::U128,
             Number::I8(_) => PrimitiveType::I32,
             Number::I16(_) => PrimitiveType::I32,
             Number::I32(_) => PrimitiveType::I32,
             Number::I64(_) => PrimitiveType::I64,
+            Number::I128(_) => PrimitiveType::I128,
+            Number::BigInt(_) => PrimitiveType::BigInt,
+            Number::Decimal(_) => PrimitiveType::Decimal,
             Number::F32(_) => PrimitiveType::F32,
             Number::F64(_) => PrimitiveType::F64,
         }
     }
 }
 
+impl Display for Number {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Number::F32(num) => write!(f, "{}", num),
+            Number::F64(num) => write!(f, "{}", num),
+            Number::U128(num) => write!(f, "{}", num),
+            Number::BigInt(num) => write!(f, "{}n", num),
+            Number::Decimal(num) => write!(f, "{}d", num),
+            _ => write!(f, "{}", self.as_int().unwrap()),
+        }
+    }
+}
+
 impl FromStr for Number {
     type Err = &'static str;
 
@@ -97,17 +325,263 @@ impl FromStr for Number {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone)]
 pub enum Type {
     Undefined,
-    Function(Box<Type>),
-    Defined(String),
+    Unit,
+    Function(Vec<Access<Type>>, Box<Type>, Effect),
+    Defined(String, Vec<Access<Type>>),
     Compound(Vec<Access<Type>>),
     Primitive(PrimitiveType),
+    Alias(String, Vec<Access<Type>>, Box<Type>),
+    Param(String),
+    Optional(Box<Type>),
+    Result(Box<Type>, Box<Type>),
+}
+
+impl Type {
+    /**
+     * Strips any alias names off the type
+     **/
+    pub fn unalias(&self) -> &Type {
+        match self {
+            Type::Alias(_, _, type_) => type_.unalias(),
+            _ => self,
+        }
+    }
+
+    /**
+     * Replaces the type parameters with the types given for them, parameters without one are kept
+     **/
+    pub fn substitute(&self, params: &HashMap<String, Type>) -> Type {
+        match self {
+            Type::Param(name) => params.get(name).cloned().unwrap_or_else(|| self.clone()),
+            Type::Function(param_types, return_type, effect) => Type::Function(
+                param_types.iter().map(|t| Access::Owned(t.map(|t| t.substitute(params)))).collect(),
+                Box::new(return_type.substitute(params)),
+                *effect,
+            ),
+            Type::Defined(name, args) => Type::Defined(
+                name.clone(),
+                args.iter().map(|t| Access::Owned(t.map(|t| t.substitute(params)))).collect(),
+            ),
+            Type::Compound(types) => types.iter()
+                .map(|t| Access::Owned(t.map(|t| t.substitute(params))))
+                .collect(),
+            Type::Alias(name, args, type_) => Type::Alias(
+                name.clone(),
+                args.iter().map(|t| Access::Owned(t.map(|t| t.substitute(params)))).collect(),
+                Box::new(type_.substitute(params)),
+            ),
+            Type::Optional(type_) => Type::Optional(Box::new(type_.substitute(params))),
+            Type::Result(value, err) => Type::Result(Box::new(value.substitute(params)), Box::new(err.substitute(params))),
+            _ => self.clone(),
+        }
+    }
+
+    /**
+     * Turns the names written in source which refer to one of the given type parameters into parameters
+     **/
+    pub fn with_params(&self, names: &[String]) -> Type {
+        let params = names.iter()
+            .map(|name| (name.clone(), Type::Param(name.clone())))
+            .collect();
+
+        self.substitute_names(&params)
+    }
+
+    fn substitute_names(&self, names: &HashMap<String, Type>) -> Type {
+        match self {
+            Type::Defined(name, args) if args.is_empty() && names.contains_key(name) => names[name].clone(),
+            Type::Defined(name, args) => Type::Defined(
+                name.clone(),
+                args.iter().map(|t| Access::Owned(t.map(|t| t.substitute_names(names)))).collect(),
+            ),
+            Type::Function(param_types, return_type, effect) => Type::Function(
+                param_types.iter().map(|t| Access::Owned(t.map(|t| t.substitute_names(names)))).collect(),
+                Box::new(return_type.substitute_names(names)),
+                *effect,
+            ),
+            Type::Compound(types) => types.iter()
+                .map(|t| Access::Owned(t.map(|t| t.substitute_names(names))))
+                .collect(),
+            Type::Optional(type_) => Type::Optional(Box::new(type_.substitute_names(names))),
+            Type::Result(value, err) => Type::Result(Box::new(value.substitute_names(names)), Box::new(err.substitute_names(names))),
+            _ => self.clone(),
+        }
+    }
+
+    /**
+     * Matches this type, which may contain type parameters, against a concrete type,
+     * recording what each parameter stands for. Returns false if the types can not match
+     **/
+    pub fn infer_params(&self, actual: &Type, params: &mut HashMap<String, Type>) -> bool {
+        match (self.unalias(), actual.unalias()) {
+            (Type::Param(name), _) => match params.get(name) {
+                // a parameter of a generic argument can share its name with the one it is bound to
+                Some(Type::Param(bound)) if bound == name => {
+                    params.insert(name.clone(), actual.clone());
+                    true
+                }
+                Some(Type::Undefined) | None => {
+                    params.insert(name.clone(), actual.clone());
+                    true
+                }
+                Some(bound) => actual == &Type::Undefined || bound == actual,
+            },
+            (Type::Undefined, _) | (_, Type::Undefined) => true,
+            (Type::Function(p1, r1, e1), Type::Function(p2, r2, e2)) => {
+                // a pure function can be used where an impure one is expected, not the other way around
+                (e1 == &Effect::Impure || e2 == &Effect::Pure)
+                    && p1.len() == p2.len()
+                    && p1.iter().zip(p2).all(|(t1, t2)| t1.map(|t1| t2.map(|t2| t1.infer_params(t2, params))))
+                    && r1.infer_params(r2, params)
+            }
+            (Type::Compound(c1), Type::Compound(c2)) => {
+                c1.len() == c2.len()
+                    && c1.iter().zip(c2).all(|(t1, t2)| t1.map(|t1| t2.map(|t2| t1.infer_params(t2, params))))
+            }
+            (Type::Optional(t1), Type::Optional(t2)) => t1.infer_params(t2, params),
+            (Type::Result(v1, e1), Type::Result(v2, e2)) => v1.infer_params(v2, params) && e1.infer_params(e2, params),
+            // a value can always be passed where an optional of it is expected
+            (Type::Optional(t1), _) => t1.infer_params(actual, params),
+            (expected, actual) => actual.assignable_to(expected),
+        }
+    }
+
+    /**
+     * Whether `as` converts a value of this type to the target type
+     **/
+    pub fn castable_to(&self, target: &Type) -> bool {
+        match (self.unalias(), target.unalias()) {
+            (Type::Undefined, _) => true,
+            // like rust, any char converts to an integer but only bytes convert to a char
+            (Type::Primitive(PrimitiveType::Char), Type::Primitive(to)) => to == &PrimitiveType::Char || to.int_range().is_some(),
+            (Type::Primitive(from), Type::Primitive(PrimitiveType::Char)) => from == &PrimitiveType::U8,
+            (Type::Primitive(from), Type::Primitive(to)) => from == to || (from.is_numeric() && to.is_numeric()),
+            (from, to) => from == to,
+        }
+    }
+
+    /**
+     * Whether a value of this type can be used where the target type is expected, as is or widened
+     **/
+    pub fn assignable_to(&self, target: &Type) -> bool {
+        match (self.unalias(), target.unalias()) {
+            (Type::Undefined, _) | (_, Type::Undefined) => true,
+            (Type::Primitive(from), Type::Primitive(to)) => from.widens_to(to),
+            (Type::Optional(from), Type::Optional(to)) => from.assignable_to(to),
+            // a value can always be used where an optional of it is expected
+            (from, Type::Optional(to)) => from.assignable_to(to),
+            (Type::Compound(c1), Type::Compound(c2)) => {
+                c1.len() == c2.len()
+                    && c1.iter().zip(c2).all(|(t1, t2)| t1.map(|t1| t2.map(|t2| t1.assignable_to(t2))))
+            }
+            (Type::Result(v1, e1), Type::Result(v2, e2)) => v1.assignable_to(v2) && e1.assignable_to(e2),
+            // parameters go the other way, the function must accept whatever the target would be given
+            (Type::Function(p1, r1, e1), Type::Function(p2, r2, e2)) => {
+                (e1 == &Effect::Pure || e2 == &Effect::Impure)
+                    && p1.len() == p2.len()
+                    && p1.iter().zip(p2).all(|(t1, t2)| t2.map(|t2| t1.map(|t1| t2.assignable_to(t1))))
+                    && r1.assignable_to(r2)
+            }
+            (from, to) => from == to,
+        }
+    }
+
+    /**
+     * Whether no part of the type is left undefined, so it can be written in source
+     **/
+    pub fn is_known(&self) -> bool {
+        let all_known = |types: &[Access<Type>]| types.iter().all(|t| t.map(Type::is_known));
+
+        match self {
+            Type::Undefined => false,
+            Type::Function(params, return_type, _) => all_known(params) && return_type.is_known(),
+            Type::Defined(_, args) | Type::Alias(_, args, _) => all_known(args),
+            Type::Compound(types) => all_known(types),
+            Type::Optional(type_) => type_.is_known(),
+            Type::Result(value, err) => value.is_known() && err.is_known(),
+            Type::Unit | Type::Primitive(_) | Type::Param(_) => true,
+        }
+    }
+
+    /**
+     * Combines two types which may each leave parts undefined, e.g. the types of `ok 1` and `err "e"`.
+     * Returns None if the types conflict
+     **/
+    pub fn merge(&self, other: &Type) -> Option<Type> {
+        match (self.unalias(), other.unalias()) {
+            (Type::Undefined, _) => Some(other.clone()),
+            (_, Type::Undefined) => Some(self.clone()),
+            (Type::Optional(t1), Type::Optional(t2)) => Some(Type::Optional(Box::new(t1.merge(t2)?))),
+            (Type::Result(v1, e1), Type::Result(v2, e2)) => Some(Type::Result(Box::new(v1.merge(v2)?), Box::new(e1.merge(e2)?))),
+            (Type::Compound(c1), Type::Compound(c2)) if c1.len() == c2.len() => c1.iter()
+                .zip(c2)
+                .map(|(t1, t2)| t1.map(|t1| t2.map(|t2| t1.merge(t2))).map(Access::Owned))
+                .collect(),
+            _ if self == other => Some(self.clone()),
+            _ => None,
+        }
+    }
+}
+
+// aliases are only names, two types are equal if their structures are
+impl PartialEq for Type {
+    fn eq(&self, other: &Self) -> bool {
+        match (self.unalias(), other.unalias()) {
+            (Type::Undefined, Type::Undefined) | (Type::Unit, Type::Unit) => true,
+            (Type::Function(p1, r1, e1), Type::Function(p2, r2, e2)) => p1 == p2 && r1 == r2 && e1 == e2,
+            (Type::Defined(n1, a1), Type::Defined(n2, a2)) => n1 == n2 && a1 == a2,
+            (Type::Compound(c1), Type::Compound(c2)) => c1 == c2,
+            (Type::Primitive(p1), Type::Primitive(p2)) => p1 == p2,
+            (Type::Param(n1), Type::Param(n2)) => n1 == n2,
+            (Type::Optional(t1), Type::Optional(t2)) => t1 == t2,
+            (Type::Result(v1, e1), Type::Result(v2, e2)) => v1 == v2 && e1 == e2,
+            _ => false,
+        }
+    }
+}
+
+// written the way types are in source, unknown types as `_`
+impl Display for Type {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        fn list(types: &[Access<Type>]) -> String {
+            types.iter().map(|t| t.to_string()).collect::<Vec<_>>().join(", ")
+        }
+
+        fn named(f: &mut Formatter<'_>, name: &str, args: &[Access<Type>]) -> std::fmt::Result {
+            if args.is_empty() {
+                write!(f, "{}", name)
+            } else {
+                write!(f, "{}({})", name, list(args))
+            }
+        }
+
+        match self {
+            Type::Undefined => write!(f, "_"),
+            Type::Unit => write!(f, "Unit"),
+            Type::Function(params, return_type, effect) => {
+                if effect == &Effect::Impure {
+                    write!(f, "impure ")?;
+                }
+
+                write!(f, "({}) -> {}", list(params), return_type)
+            }
+            Type::Defined(name, args) | Type::Alias(name, args, _) => named(f, name, args),
+            Type::Compound(types) => write!(f, "({})", list(types)),
+            Type::Primitive(prim) => write!(f, "{}", prim),
+            Type::Param(name) => write!(f, "{}", name),
+            // `?` would otherwise apply to the return type
+            Type::Optional(type_) if matches!(type_.as_ref(), Type::Function(..)) => write!(f, "Option({})", type_),
+            Type::Optional(type_) => write!(f, "{}?", type_),
+            Type::Result(value, err) => write!(f, "Result({}, {})", value, err),
+        }
+    }
 }
 
 lazy_static! {
-    static ref PRIM_TYPES: HashMap<String, PrimitiveType> = {
+    pub static ref PRIM_TYPES: HashMap<String, PrimitiveType> = {
         let mut map = HashMap::new();
         for t in PrimitiveType::iter() {
             map.insert(t.to_string(), t);
@@ -121,7 +595,8 @@ impl From<String> for Type {
     fn from(value: String) -> Self {
         match PRIM_TYPES.get(&value) {
             Some(prim) => Type::Primitive(prim.clone()),
-            None => Type::Defined(value),
+            None if value == "Unit" => Type::Unit,
+            None => Type::Defined(value, vec![]),
         }
     }
 }
@@ -135,115 +610,447 @@ impl FromIterator<Access<Type>> for Type {
     }
 }
 
-impl PartialOrd for PrimitiveType {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        let self_size = match self {
-            PrimitiveType::U8 | PrimitiveType::I8 => 1,
-            PrimitiveType::U16 | PrimitiveType::I16 => 2,
-            PrimitiveType::U32 | PrimitiveType::I32 | PrimitiveType::F32 => 4,
-            PrimitiveType::U64 | PrimitiveType::I64 | PrimitiveType::F64 => 8,
-            PrimitiveType::Str => return None
-        };
-
-        let other_size = match other {
-            PrimitiveType::U8 | PrimitiveType::I8 => 1,
-            PrimitiveType::U16 | PrimitiveType::I16 => 2,
-            PrimitiveType::U32 | PrimitiveType::I32 | PrimitiveType::F32 => 4,
-            PrimitiveType::U64 | PrimitiveType::I64 | PrimitiveType::F64 => 8,
-            PrimitiveType::Str => return None
-        };
-
-        self_size.partial_cmp(&other_size)
-    }
-}
-
-impl PartialOrd for Type {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        match (self, other) {
-            (Type::Primitive(pt1), Type::Primitive(pt2)) => pt1.partial_cmp(pt2),
-            _ => None,
-        }
-    }
+/**
+ * Whether calling a function may perform impure calls, functions are impure if any call in their body is
+ **/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Effect {
+    Pure,
+    Impure,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, EnumIter, Display)]
+#[derive(Debug, Clone, PartialEq, Eq, EnumIter, strum::Display)]
 pub enum PrimitiveType {
     U8,
     U16,
     U32,
     U64,
+    U128,
     I8,
     I16,
     I32,
     I64,
+    I128,
     F32,
     F64,
+    // arbitrary precision, lowered to num_bigint::BigInt
+    BigInt,
+    // exact base 10, lowered to rust_decimal::Decimal
+    Decimal,
+    Char,
     Str,
 }
 
+impl PrimitiveType {
+    pub fn is_numeric(&self) -> bool {
+        !matches!(self, PrimitiveType::Char | PrimitiveType::Str)
+    }
+
+    /**
+     * Whether every value of this type converts to the other type without loss
+     **/
+    pub fn widens_to(&self, to: &PrimitiveType) -> bool {
+        if self == to {
+            return true;
+        }
+
+        let Some((min, max)) = self.int_range() else {
+            return matches!((self, to), (PrimitiveType::F32, PrimitiveType::F64));
+        };
+
+        if let Some((to_min, to_max)) = to.int_range() {
+            return to_min <= min && max <= to_max;
+        }
+
+        // the largest integer each type represents exactly
+        let exact = match to {
+            PrimitiveType::BigInt => return true,
+            PrimitiveType::F32 => 1 << f32::MANTISSA_DIGITS,
+            PrimitiveType::F64 => 1 << f64::MANTISSA_DIGITS,
+            PrimitiveType::Decimal => Decimal::MAX.to_u128().unwrap(),
+            _ => return false,
+        };
+
+        min.unsigned_abs() <= exact && max <= exact
+    }
+
+    /**
+     * The inclusive range of values an integer type can hold, `None` for non integer types
+     **/
+    pub fn int_range(&self) -> Option<(i128, u128)> {
+        match self {
+            PrimitiveType::U8 => Some((u8::MIN as i128, u8::MAX as u128)),
+            PrimitiveType::U16 => Some((u16::MIN as i128, u16::MAX as u128)),
+            PrimitiveType::U32 => Some((u32::MIN as i128, u32::MAX as u128)),
+            PrimitiveType::U64 => Some((u64::MIN as i128, u64::MAX as u128)),
+            PrimitiveType::U128 => Some((u128::MIN as i128, u128::MAX)),
+            PrimitiveType::I8 => Some((i8::MIN as i128, i8::MAX as u128)),
+            PrimitiveType::I16 => Some((i16::MIN as i128, i16::MAX as u128)),
+            PrimitiveType::I32 => Some((i32::MIN as i128, i32::MAX as u128)),
+            PrimitiveType::I64 => Some((i64::MIN as i128, i64::MAX as u128)),
+            PrimitiveType::I128 => Some((i128::MIN, i128::MAX as u128)),
+            _ => None,
+        }
+    }
+
+    /**
+     * The inclusive range of finite values a floating point type can hold, `None` for non floating point types
+     **/
+    pub fn float_range(&self) -> Option<(f64, f64)> {
+        match self {
+            PrimitiveType::F32 => Some((f32::MIN as f64, f32::MAX as f64)),
+            PrimitiveType::F64 => Some((f64::MIN, f64::MAX)),
+            _ => None,
+        }
+    }
+}
+
+pub(crate) enum ResolveErr {
+    UndefinedType(String),
+    TypeArgCount {
+        name: String,
+        expected: usize,
+        found: usize,
+    },
+}
+
+#[derive(Debug)]
+pub(crate) struct TraitImpl {
+    pub trait_name: String,
+    pub target: Type,
+    // each method's type and symbol
+    pub methods: HashMap<String, (Type, usize)>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Binding {
+    pub type_: Access<Type>,
+    pub mutable: bool,
+    pub span: Span,
+    // the symbol recorded for the binding, none for builtins
+    pub symbol: Option<usize>,
+}
+
 #[derive(Debug)]
 pub(crate) struct TypeEnvironment {
-    types: HashMap<Access<String>, Access<Type>>,
+    scopes: Vec<HashMap<Access<String>, Binding>>,
+    aliases: HashMap<String, (Vec<String>, Type)>,
+    // each method's name, signature and symbol
+    traits: HashMap<String, Vec<(String, Type, usize)>>,
+    impls: Vec<TraitImpl>,
+    functions: Vec<FunctionContext>,
+    symbols: SymbolTable,
+    // names used where nothing binds them yet, with the index of the scope they are used in
+    unbound: Vec<(String, Span, usize)>,
+    undefined: Vec<(String, Span)>,
+}
+
+/**
+ * What is learned about a function literal while typing its body
+ **/
+#[derive(Debug, Default)]
+pub(crate) struct FunctionContext {
+    pub declared_pure: bool,
+    pub impure: bool,
+    // error types propagated with `?`
+    pub propagated: Vec<(Type, Span)>,
 }
 
 impl TypeEnvironment {
     pub fn new() -> Self {
         TypeEnvironment {
-            types: HashMap::new()
+            scopes: vec![TypeEnvironment::builtins(), HashMap::new()],
+            aliases: HashMap::new(),
+            traits: HashMap::new(),
+            impls: vec![],
+            functions: vec![],
+            symbols: SymbolTable::default(),
+            unbound: vec![],
+            undefined: vec![],
         }
     }
 
     pub fn clear(&mut self) {
-        self.types.clear();
+        self.scopes = vec![TypeEnvironment::builtins(), HashMap::new()];
+        self.aliases.clear();
+        self.traits.clear();
+        self.impls.clear();
+        self.functions.clear();
+        self.symbols.clear();
+        self.unbound.clear();
+        self.undefined.clear();
     }
 
-    pub fn assign(&mut self, ident: Access<String>, type_: Access<Type>) {
-        self.types.insert(ident, type_);
+    /**
+     * The outermost scope, kept apart from the top level of a program so builtins can be shadowed
+     **/
+    fn builtins() -> HashMap<Access<String>, Binding> {
+        let param = |name: &str| Type::Param(name.to_string());
+        let builtin = |type_| Binding { type_: Access::Owned(type_), mutable: false, span: Span::default(), symbol: None };
+        let mut builtins = HashMap::new();
+
+        builtins.insert(Access::Owned("ok".to_string()), builtin(Type::Function(
+            vec![Access::Owned(param("T"))],
+            Box::new(Type::Result(Box::new(param("T")), Box::new(Type::Undefined))),
+            Effect::Pure,
+        )));
+        builtins.insert(Access::Owned("err".to_string()), builtin(Type::Function(
+            vec![Access::Owned(param("E"))],
+            Box::new(Type::Result(Box::new(Type::Undefined), Box::new(param("E")))),
+            Effect::Pure,
+        )));
+
+        builtins
     }
 
-    pub fn predict_type(expr: &Expression) -> Access<Type> {
-        match expr {
-            Expression::MathExpr(expr) => TypeEnvironment::type_math_expr(expr),
-            Expression::StrLiteral(_) => Access::Owned(Type::Primitive(PrimitiveType::Str)),
-            Expression::Compound(c) => c.iter()
-                .map(|expr| TypeEnvironment::predict_type(expr))
-                .collect::<Type>()
-                .into(),
-            Expression::FuncLiteral { return_type, .. } => Access::Owned(Type::Function(Box::new(return_type.clone()))),
+    pub fn push_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    pub fn pop_scope(&mut self) {
+        self.resolve_unbound();
+        self.scopes.pop();
+    }
+
+    /**
+     * Records a name used before anything binds it, which a function body may still refer to if it is bound later
+     **/
+    pub fn use_unbound(&mut self, name: &str, span: Span) {
+        self.unbound.push((name.to_string(), span, self.scopes.len() - 1));
+    }
+
+    /**
+     * Settles the unbound names with the bindings of the innermost scope before it is left.
+     * A name bound later in the scope it is used in is used too early, a function nested in the scope may use it
+     **/
+    fn resolve_unbound(&mut self) {
+        let depth = self.scopes.len() - 1;
+        let scope = &self.scopes[depth];
+        let mut undefined = vec![];
+
+        self.unbound.retain(|(name, span, used_in)| {
+            if *used_in < depth || !scope.contains_key(&Access::Owned(name.clone())) {
+                return true;
+            }
+
+            if *used_in == depth {
+                undefined.push((name.clone(), *span));
+            }
+            false
+        });
+
+        self.undefined.extend(undefined);
+    }
+
+    /**
+     * The names which were never bound where they are used, once the whole program is typed
+     **/
+    pub fn take_undefined(&mut self) -> Vec<(String, Span)> {
+        self.resolve_unbound();
+
+        let mut undefined: Vec<(String, Span)> = self.undefined.drain(..)
+            .chain(self.unbound.drain(..).map(|(name, span, _)| (name, span)))
+            .collect();
+        undefined.sort_by_key(|(_, span)| (span.line, span.start));
+        undefined
+    }
+
+    pub fn assign(&mut self, ident: Access<String>, binding: Binding) {
+        self.scopes.last_mut().unwrap().insert(ident, binding);
+    }
+
+    pub fn lookup(&self, ident: &Access<String>) -> Option<&Binding> {
+        self.scopes.iter()
+            .rev()
+            .find_map(|scope| scope.get(ident))
+    }
+
+    /**
+     * Finds a binding in the innermost scope only, bindings of enclosing scopes are shadowed rather than reassigned
+     **/
+    pub fn lookup_local(&self, ident: &Access<String>) -> Option<&Binding> {
+        self.scopes.last().and_then(|scope| scope.get(ident))
+    }
+
+    /**
+     * Binds a new name in the current scope, recording it as a symbol
+     **/
+    pub fn declare(&mut self, name: &Rc<String>, kind: SymbolKind, type_: Access<Type>, mutable: bool, span: Span) {
+        let symbol = self.symbols.declare(Symbol {
+            mutable,
+            ..Symbol::new(name.to_string(), kind, span, type_.map(Clone::clone))
+        });
+
+        self.assign(name.into(), Binding { type_, mutable, span, symbol: Some(symbol) });
+    }
+
+    pub fn symbols(&self) -> &SymbolTable {
+        &self.symbols
+    }
+
+    pub fn symbols_mut(&mut self) -> &mut SymbolTable {
+        &mut self.symbols
+    }
+
+    pub fn push_function(&mut self, declared_pure: bool) {
+        self.functions.push(FunctionContext { declared_pure, ..Default::default() });
+    }
+
+    pub fn pop_function(&mut self) -> FunctionContext {
+        self.functions.pop().unwrap_or_default()
+    }
+
+    /**
+     * Records an error type propagated from the enclosing function, returns false outside of a function
+     **/
+    pub fn propagate(&mut self, err: Type, span: Span) -> bool {
+        match self.functions.last_mut() {
+            Some(function) => {
+                function.propagated.push((err, span));
+                true
+            }
+            None => false,
         }
     }
 
-    fn type_ident(ident: &Identifier) -> Access<Type> {
-        match ident {
-            Identifier::Identifier(_, t) => Access::Rc(t.clone()),
-            Identifier::Compound(vec) => vec.iter()
-                .map(|id| TypeEnvironment::type_ident(id))
-                .collect::<Type>()
-                .into(),
-            _ => todo!()
+    /**
+     * Marks the enclosing function as impure, returns false if it was declared pure.
+     * A function declared pure keeps its declared effect, so only the call is reported
+     **/
+    pub fn perform_impure(&mut self) -> bool {
+        match self.functions.last_mut() {
+            Some(function) if function.declared_pure => false,
+            Some(function) => {
+                function.impure = true;
+                true
+            }
+            None => true,
         }
     }
 
-    fn type_math_expr(expr: &MathExpr) -> Access<Type> {
-        match expr {
-            MathExpr::Literal(literal) => Access::Owned(Type::Primitive(literal.prim_type())),
-            MathExpr::Identifier(ident) => TypeEnvironment::type_ident(ident),
-            MathExpr::BinaryOp(lhs, rhs, _) => {
-                let lhs_type = TypeEnvironment::type_math_expr(lhs);
-                let rhs_type = TypeEnvironment::type_math_expr(rhs);
+    pub fn assign_alias(&mut self, name: String, params: Vec<String>, type_: Type) {
+        self.aliases.insert(name, (params, type_));
+    }
+
+    pub fn assign_trait(&mut self, name: String, methods: Vec<(String, Type, usize)>) {
+        self.traits.insert(name, methods);
+    }
+
+    pub fn lookup_trait(&self, name: &str) -> Option<&Vec<(String, Type, usize)>> {
+        self.traits.get(name)
+    }
+
+    pub fn assign_impl(&mut self, trait_impl: TraitImpl) {
+        self.impls.push(trait_impl);
+    }
+
+    pub fn lookup_impl(&self, trait_name: &str, target: &Type) -> Option<&TraitImpl> {
+        self.impls.iter().find(|i| i.trait_name == trait_name && &i.target == target)
+    }
+
+    /**
+     * Finds the implementation of a method for a type, statically dispatching on the type
+     **/
+    pub fn lookup_method(&self, target: &Type, method: &str) -> Option<&(Type, usize)> {
+        self.impls.iter()
+            .filter(|i| &i.target == target)
+            .find_map(|i| i.methods.get(method))
+    }
+
+    /**
+     * Returns true if any trait declares a method with the name
+     **/
+    pub fn is_method(&self, method: &str) -> bool {
+        self.traits.values().any(|methods| methods.iter().any(|(name, _, _)| name == method))
+    }
+
+    /**
+     * Replaces every alias name in the type with the aliased type, keeping the name for diagnostics
+     **/
+    pub fn resolve(&self, type_: &Type) -> Result<Type, ResolveErr> {
+        Ok(match type_ {
+            Type::Defined(name, args) if name == "Option" && !self.aliases.contains_key(name) => {
+                let [arg] = args.as_slice() else {
+                    return Err(ResolveErr::TypeArgCount { name: name.clone(), expected: 1, found: args.len() });
+                };
 
-                if lhs_type == rhs_type {
-                    lhs_type
-                } else if lhs_type < rhs_type {
-                    rhs_type
-                } else if lhs_type > rhs_type {
-                    lhs_type
-                } else {
-                    todo!()
+                Type::Optional(Box::new(arg.map(|t| self.resolve(t))?))
+            }
+            Type::Defined(name, args) if name == "Result" && !self.aliases.contains_key(name) => {
+                let [value, err] = args.as_slice() else {
+                    return Err(ResolveErr::TypeArgCount { name: name.clone(), expected: 2, found: args.len() });
+                };
+
+                Type::Result(Box::new(value.map(|t| self.resolve(t))?), Box::new(err.map(|t| self.resolve(t))?))
+            }
+            Type::Defined(name, args) => {
+                let Some((params, aliased)) = self.aliases.get(name) else {
+                    if PRIM_TYPES.contains_key(name) {
+                        return Err(ResolveErr::TypeArgCount { name: name.clone(), expected: 0, found: args.len() });
+                    }
+
+                    return Err(ResolveErr::UndefinedType(name.clone()));
+                };
+
+                if params.len() != args.len() {
+                    return Err(ResolveErr::TypeArgCount {
+                        name: name.clone(),
+                        expected: params.len(),
+                        found: args.len(),
+                    });
                 }
+
+                let args = args.iter()
+                    .map(|t| t.map(|t| self.resolve(t)))
+                    .collect::<Result<Vec<Type>, ResolveErr>>()?;
+                let type_args = params.iter()
+                    .cloned()
+                    .zip(args.iter().cloned())
+                    .collect();
+
+                Type::Alias(name.clone(), args.into_iter().map(Access::Owned).collect(), Box::new(aliased.substitute(&type_args)))
             }
-            MathExpr::Negate(expr) => TypeEnvironment::type_math_expr(expr),
-            MathExpr::FuncCall(ident, _) => TypeEnvironment::type_ident(ident)
+            Type::Function(params, return_type, effect) => Type::Function(
+                params.iter()
+                    .map(|t| t.map(|t| self.resolve(t)).map(Access::Owned))
+                    .collect::<Result<Vec<Access<Type>>, ResolveErr>>()?,
+                Box::new(self.resolve(return_type)?),
+                *effect,
+            ),
+            Type::Compound(types) => types.iter()
+                .map(|t| t.map(|t| self.resolve(t)).map(Access::Owned))
+                .collect::<Result<Type, ResolveErr>>()?,
+            Type::Optional(type_) => Type::Optional(Box::new(self.resolve(type_)?)),
+            Type::Result(value, err) => Type::Result(Box::new(self.resolve(value)?), Box::new(self.resolve(err)?)),
+            _ => type_.clone(),
+        })
+    }
+
+    /**
+     * Turns unknown names without type arguments inside function signatures into type parameters,
+     * so `(T) -> T` is generic over `T`
+     **/
+    pub fn generalize(&self, type_: &Type, in_signature: bool) -> Type {
+        match type_ {
+            Type::Defined(name, args) if in_signature && args.is_empty() && !self.aliases.contains_key(name) => Type::Param(name.clone()),
+            Type::Defined(name, args) => Type::Defined(
+                name.clone(),
+                args.iter().map(|t| Access::Owned(t.map(|t| self.generalize(t, in_signature)))).collect(),
+            ),
+            Type::Function(params, return_type, effect) => Type::Function(
+                params.iter().map(|t| Access::Owned(t.map(|t| self.generalize(t, true)))).collect(),
+                Box::new(self.generalize(return_type, true)),
+                *effect,
+            ),
+            Type::Compound(types) => types.iter()
+                .map(|t| Access::Owned(t.map(|t| self.generalize(t, in_signature))))
+                .collect(),
+            Type::Optional(type_) => Type::Optional(Box::new(self.generalize(type_, in_signature))),
+            Type::Result(value, err) => Type::Result(
+                Box::new(self.generalize(value, in_signature)),
+                Box::new(self.generalize(err, in_signature)),
+            ),
+            _ => type_.clone(),
         }
     }
 }
@@ -1,41 +1,906 @@
+use std::collections::HashMap;
+use std::rc::Rc;
 use crate::error::ParserErr;
-use crate::nodes::{AstNode, Expression, Identifier};
-use crate::types::{Type, TypeEnvironment};
+use crate::nodes::{AstNode, Expression, Identifier, MathExpr, Span};
+use crate::symbols::{Symbol, SymbolKind};
+use crate::types::{Binding, Effect, Number, PrimitiveType, ResolveErr, TraitImpl, Type, TypeEnvironment};
 use crate::util::Access;
 
 pub(crate) struct PropsTyper;
 impl PropsTyper {
-    pub fn process(&self, ast: &Vec<AstNode>, type_environment: &mut TypeEnvironment, errs: &mut Vec<ParserErr>) {
-        for node in ast {
-            if let AstNode::Assignment(ident, expr) = node {
-                self.assign_type(ident, type_environment, expr, errs);
+    pub fn process(&self, ast: &[AstNode], type_environment: &mut TypeEnvironment, errs: &mut Vec<ParserErr>) {
+        self.type_statements(ast, type_environment, errs);
+
+        for (name, span) in type_environment.take_undefined() {
+            errs.push(ParserErr::UndefinedBinding { name, span });
+        }
+    }
+
+    /**
+     * Types each statement in order, returning the type of the first returned value
+     **/
+    fn type_statements(&self, statements: &[AstNode], type_environment: &mut TypeEnvironment, errs: &mut Vec<ParserErr>) -> Access<Type> {
+        let mut return_type = None;
+
+        for node in statements {
+            match node {
+                AstNode::Assignment(ident, expr) => self.assign_type(ident, false, type_environment, expr, errs),
+                AstNode::Return(expr, span) => {
+                    let type_ = self.predict_type(expr, None, type_environment, errs);
+
+                    // returns may each fill in part of the type, e.g. `return ok x` and `return err e`
+                    match &return_type {
+                        None => return_type = Some(type_),
+                        Some(first) => match first.map(|t1| type_.map(|t2| t1.merge(t2))) {
+                            Some(merged) => return_type = Some(Access::Owned(merged)),
+                            None => errs.push(ParserErr::UnmatchedTypes {
+                                type_1: type_,
+                                type_2: first.clone(),
+                                span: *span,
+                            }),
+                        },
+                    }
+                }
+                AstNode::TypeAlias(name, params, type_, span) => {
+                    let type_ = self.resolve_type(&type_.with_params(params), false, *span, type_environment, errs);
+                    type_environment.symbols_mut().declare(Symbol::new(name.clone(), SymbolKind::Type, *span, type_.clone()));
+                    type_environment.assign_alias(name.clone(), params.clone(), type_);
+                }
+                AstNode::Trait(name, methods, span) => {
+                    type_environment.symbols_mut().declare(Symbol::new(name.clone(), SymbolKind::Trait, *span, Type::Undefined));
+
+                    // `Self` is not a known type so it becomes a parameter of each method's signature
+                    let methods = methods.iter()
+                        .filter_map(|method| match method {
+                            Identifier::Identifier(method, t, span) => {
+                                let type_ = self.resolve_type(t, true, *span, type_environment, errs);
+                                let symbol = type_environment.symbols_mut().declare(Symbol::new(method.to_string(), SymbolKind::Method, *span, type_.clone()));
+                                Some((method.to_string(), type_, symbol))
+                            }
+                            _ => None,
+                        })
+                        .collect();
+
+                    type_environment.assign_trait(name.clone(), methods);
+                }
+                AstNode::Impl(name, target, methods, span) => self.type_impl(name, target, methods, *span, type_environment, errs),
+                AstNode::ImpFuncCall(ident, args) => {
+                    // the statement discards the value, so the call may return anything
+                    self.type_call(ident, args, type_environment, errs);
+                    self.perform_impure(ident.span(), type_environment, errs);
+                }
+                AstNode::Expression(expr) => {
+                    self.predict_type(expr, None, type_environment, errs);
+                }
             }
         }
+
+        // statements without a return evaluate to nothing
+        return_type.unwrap_or(Access::Owned(Type::Unit))
     }
 
-    fn assign_type(&self, ident: &Identifier, type_environment: &mut TypeEnvironment, expr: &Expression, errs: &mut Vec<ParserErr>) {
-        let expr_type = TypeEnvironment::predict_type(expr);
-        
+    fn assign_type(&self, ident: &Identifier, mutable: bool, type_environment: &mut TypeEnvironment, expr: &Expression, errs: &mut Vec<ParserErr>) {
         match ident {
-            Identifier::Identifier(str, t) => match **t {
-                Type::Undefined => type_environment.assign(str.into(), expr_type),
+            Identifier::Identifier(str, t, span) => {
+                let t = self.resolve_type(t, false, *span, type_environment, errs);
+
+                // reassigning a mutable binding is checked against its type like an annotation
+                let previous = type_environment.lookup_local(&str.into())
+                    .filter(|previous| previous.mutable && t == Type::Undefined)
+                    .cloned();
+                let expected = match &previous {
+                    Some(previous) => previous.type_.map(Clone::clone),
+                    None => t,
+                };
+
+                let expr_type = self.predict_type(expr, Some(&expected).filter(|t| **t != Type::Undefined), type_environment, errs);
+                if expected == Type::Undefined {
+                    self.bind(str, expr_type, mutable, *span, type_environment, errs);
+                    self.bind_body(str, *span, expr, type_environment);
+                    return;
+                }
+
+                self.check_literal_ranges(expr, &expected, errs);
+
+                if !expr_type.map(|t| self.value_assignable(expr, t, &expected)) {
+                    let is_optional = |t: &Type| matches!(t.unalias(), Type::Optional(_));
+
+                    errs.push(match previous {
+                        Some(previous) => ParserErr::ReassignedType {
+                            name: str.to_string(),
+                            expected: previous.type_,
+                            found: expr_type.clone(),
+                            span: *span,
+                            previous: previous.span,
+                        },
+                        None if expr_type.map(is_optional) && !is_optional(&expected) => ParserErr::UnwrappedOptional {
+                            type_: expr_type.clone(),
+                            span: *span,
+                        },
+                        None => ParserErr::UnmatchedTypes {
+                            type_1: expr_type.clone(),
+                            type_2: Access::Owned(expected.clone()),
+                            span: *span,
+                        },
+                    });
+                }
+
+                // the annotation is the binding's type even if the value does not match, so uses are checked against it
+                self.bind(str, Access::Owned(expected.clone()), mutable, *span, type_environment, errs);
+
+                let declared = type_environment.lookup_local(&str.into())
+                    .filter(|binding| binding.span == *span)
+                    .and_then(|binding| binding.symbol);
+                if let Some(symbol) = declared {
+                    let symbol = type_environment.symbols_mut().symbol_mut(symbol);
+                    // a literal takes the annotated type rather than the type it would have on its own
+                    symbol.type_ = match expr {
+                        Expression::MathExpr(expr) if self.literal_fits(expr, &expected) => expected.clone(),
+                        _ => expr_type.map(Clone::clone),
+                    };
+                    symbol.annotation = Some(expected);
+                }
+
+                self.bind_body(str, *span, expr, type_environment);
+            }
+            Identifier::Compound(idents) => match expr {
+                // destructure element by element so each literal is checked against its own identifier
+                Expression::Compound(exprs) => {
+                    if idents.len() != exprs.len() {
+                        errs.push(ParserErr::UnmatchedArity {
+                            expected: idents.len(),
+                            found: exprs.len(),
+                            span: ident.span(),
+                        });
+                    }
+
+                    for (ident, expr) in idents.iter().zip(exprs) {
+                        self.assign_type(ident, mutable, type_environment, expr, errs);
+                    }
+                }
                 _ => {
-                    if expr_type.map(|v| v == t.as_ref()) {
-                        type_environment.assign(str.into(), t.into());
+                    let expr_type = self.predict_type(expr, None, type_environment, errs);
+                    self.destructure_type(ident, mutable, expr_type, type_environment, errs);
+                }
+            },
+            Identifier::Mutable(ident) => self.assign_type(ident, true, type_environment, expr, errs),
+            Identifier::Accessor(..) | Identifier::SafeAccessor(..) => {
+                let expr_type = self.predict_type(expr, None, type_environment, errs);
+                self.destructure_type(ident, mutable, expr_type, type_environment, errs);
+            }
+        }
+    }
+
+    fn type_impl(&self, trait_name: &str, target: &Type, methods: &[AstNode], span: Span, type_environment: &mut TypeEnvironment, errs: &mut Vec<ParserErr>) {
+        let Some(signatures) = type_environment.lookup_trait(trait_name).cloned() else {
+            errs.push(ParserErr::UndefinedTrait {
+                name: trait_name.to_string(),
+                span,
+            });
+            return;
+        };
+
+        let target = self.resolve_type(target, false, span, type_environment, errs);
+        if type_environment.lookup_impl(trait_name, &target).is_some() {
+            errs.push(ParserErr::ConflictingImpl {
+                trait_name: trait_name.to_string(),
+                type_: Access::Owned(target.clone()),
+                span,
+            });
+        }
+
+        let self_type = HashMap::from([("Self".to_string(), target.clone())]);
+        let mut implemented = HashMap::new();
+
+        // methods are only reachable through the type, keep them out of the enclosing scope
+        type_environment.push_scope();
+
+        for node in methods {
+            let AstNode::Assignment(Identifier::Identifier(method, _, method_span), expr) = node else {
+                self.type_statements(std::slice::from_ref(node), type_environment, errs);
+                continue;
+            };
+
+            let Some((_, signature, signature_symbol)) = signatures.iter().find(|(name, _, _)| name == method.as_str()) else {
+                errs.push(ParserErr::UnknownMethod {
+                    trait_name: trait_name.to_string(),
+                    method: method.to_string(),
+                    span: *method_span,
+                });
+                continue;
+            };
+
+            let expected = signature.substitute(&self_type);
+            let method_type = self.predict_type(expr, Some(&expected), type_environment, errs);
+
+            if !method_type.map(|t| expected.infer_params(t, &mut HashMap::new())) {
+                errs.push(ParserErr::UnmatchedTypes {
+                    type_1: method_type,
+                    type_2: Access::Owned(expected.clone()),
+                    span: *method_span,
+                });
+            }
+
+            let symbol = type_environment.symbols_mut().declare(Symbol::new(method.to_string(), SymbolKind::Method, *method_span, expected.clone()));
+            // an implementation refers to the signature it fills in
+            type_environment.symbols_mut().reference(*method_span, *signature_symbol);
+            if let Expression::FuncLiteral { span, .. } = expr {
+                type_environment.symbols_mut().bind_body(symbol, *span);
+            }
+            implemented.insert(method.to_string(), (expected, symbol));
+        }
+
+        type_environment.pop_scope();
+
+        for (method, _, _) in signatures.iter() {
+            if !implemented.contains_key(method) {
+                errs.push(ParserErr::MissingMethod {
+                    trait_name: trait_name.to_string(),
+                    method: method.clone(),
+                    type_: Access::Owned(target.clone()),
+                    span,
+                });
+            }
+        }
+
+        type_environment.assign_impl(TraitImpl {
+            trait_name: trait_name.to_string(),
+            target,
+            methods: implemented,
+        });
+    }
+
+    /**
+     * Binds an identifier, or each identifier of a compound, to the matching part of an already typed value
+     **/
+    fn destructure_type(&self, ident: &Identifier, mutable: bool, type_: Access<Type>, type_environment: &mut TypeEnvironment, errs: &mut Vec<ParserErr>) {
+        match ident {
+            Identifier::Identifier(str, t, span) => match self.resolve_type(t, false, *span, type_environment, errs) {
+                Type::Undefined => self.bind(str, type_, mutable, *span, type_environment, errs),
+                t => {
+                    if type_.map(|v| v.assignable_to(&t)) {
+                        self.bind(str, Access::Owned(t), mutable, *span, type_environment, errs);
+                    } else {
+                        errs.push(ParserErr::UnmatchedTypes {
+                            type_1: type_,
+                            type_2: Access::Owned(t),
+                            span: *span,
+                        });
                     }
-                    
+                }
+            },
+            Identifier::Compound(idents) => {
+                let types = match type_.map(|t| t.unalias().clone()) {
+                    Type::Compound(types) => types,
+                    // nothing is known about the value, so nothing is known about its parts either
+                    Type::Undefined => vec![Access::Owned(Type::Undefined); idents.len()],
+                    _ => {
+                        errs.push(ParserErr::NotCompound {
+                            type_,
+                            expected: idents.len(),
+                            span: ident.span(),
+                        });
+                        return;
+                    }
+                };
+
+                if idents.len() != types.len() {
+                    errs.push(ParserErr::UnmatchedArity {
+                        expected: idents.len(),
+                        found: types.len(),
+                        span: ident.span(),
+                    });
+                }
+
+                for (ident, type_) in idents.iter().zip(types) {
+                    self.destructure_type(ident, mutable, type_, type_environment, errs);
+                }
+            }
+            Identifier::Mutable(ident) => self.destructure_type(ident, true, type_, type_environment, errs),
+            // assigning a field checks the value against the field's type, nothing new is bound
+            Identifier::Accessor(..) | Identifier::SafeAccessor(..) => {
+                let field = self.type_ident(ident, type_environment, errs);
+                if !type_.map(|t| field.map(|field| t.assignable_to(field))) {
                     errs.push(ParserErr::UnmatchedTypes {
-                        type_1: expr_type,
-                        type_2: Access::Rc(t.clone()),
+                        type_1: type_,
+                        type_2: field,
+                        span: ident.span(),
                     });
                 }
+            }
+        }
+    }
+
+    /**
+     * Links a binding declared at a span to the function literal assigned to it
+     **/
+    fn bind_body(&self, name: &Rc<String>, span: Span, expr: &Expression, type_environment: &mut TypeEnvironment) {
+        let Expression::FuncLiteral { span: start, .. } = expr else {
+            return;
+        };
+
+        let declared = type_environment.lookup_local(&name.into())
+            .filter(|binding| binding.span == span)
+            .and_then(|binding| binding.symbol);
+        if let Some(symbol) = declared {
+            type_environment.symbols_mut().bind_body(symbol, *start);
+        }
+    }
+
+    /**
+     * Binds a name in the current scope, checking a reassignment against the previous binding
+     **/
+    fn bind(&self, name: &Rc<String>, type_: Access<Type>, mutable: bool, span: Span, type_environment: &mut TypeEnvironment, errs: &mut Vec<ParserErr>) {
+        let Some(previous) = type_environment.lookup_local(&name.into()).cloned() else {
+            type_environment.declare(name, SymbolKind::Variable, type_, mutable, span);
+            return;
+        };
+
+        if let Some(symbol) = previous.symbol {
+            type_environment.symbols_mut().reference(span, symbol);
+        }
+
+        if !previous.mutable {
+            errs.push(ParserErr::ImmutableReassignment {
+                name: name.to_string(),
+                span,
+                previous: previous.span,
+            });
+            return;
+        }
+
+        if !type_.map(|t| previous.type_.map(|previous| t.assignable_to(previous))) {
+            errs.push(ParserErr::ReassignedType {
+                name: name.to_string(),
+                expected: previous.type_,
+                found: type_,
+                span,
+                previous: previous.span,
+            });
+            return;
+        }
+
+        // the binding keeps its type and stays declared where it was first assigned
+        if previous.type_.map(|t| t != &Type::Undefined) {
+            return;
+        }
+
+        if let Some(symbol) = previous.symbol {
+            type_environment.symbols_mut().symbol_mut(symbol).type_ = type_.map(Clone::clone);
+        }
+
+        type_environment.assign(name.into(), Binding { type_, ..previous });
+    }
+
+    /**
+     * Whether the value of an expression can be assigned to the expected type. Unlike its type alone
+     * this lets number literals take any numeric type, their ranges are checked separately
+     **/
+    fn value_assignable(&self, expr: &Expression, type_: &Type, expected: &Type) -> bool {
+        match (expr, type_.unalias(), expected.unalias()) {
+            (Expression::Compound(exprs), Type::Compound(types), Type::Compound(expected_types))
+                if exprs.len() == types.len() && types.len() == expected_types.len() => exprs.iter()
+                    .zip(types)
+                    .zip(expected_types)
+                    .all(|((expr, t), expected)| t.map(|t| expected.map(|expected| self.value_assignable(expr, t, expected)))),
+            (Expression::MathExpr(math), _, _) if self.literal_fits(math, expected) => true,
+            _ => type_.assignable_to(expected),
+        }
+    }
+
+    fn literal_fits(&self, expr: &MathExpr, expected: &Type) -> bool {
+        let prim = match expected.unalias() {
+            Type::Optional(type_) => return self.literal_fits(expr, type_),
+            Type::Primitive(prim) => prim,
+            _ => return false,
+        };
+
+        match expr {
+            MathExpr::Literal(num, _) => match num {
+                Number::F32(_) | Number::F64(_) => prim.float_range().is_some() || prim == &PrimitiveType::Decimal,
+                // suffixed literals have the type they were written with
+                Number::BigInt(_) | Number::Decimal(_) => prim == &num.prim_type(),
+                _ => prim.is_numeric(),
             },
-            Identifier::Compound(idents) => {
-                if expr_type.map(|v| match v { Type::Compound(_) => true, _ => false }) { 
-                    
+            MathExpr::Negate(expr) => self.literal_fits(expr, expected),
+            MathExpr::BinaryOp(lhs, rhs, _) => self.literal_fits(lhs, expected) && self.literal_fits(rhs, expected),
+            _ => false,
+        }
+    }
+
+    /**
+     * Resolves the aliases in a type written in source, reporting unknown type names at the given span.
+     * Unknown names in function signatures are type parameters instead
+     **/
+    fn resolve_type(&self, type_: &Type, in_signature: bool, span: Span, type_environment: &TypeEnvironment, errs: &mut Vec<ParserErr>) -> Type {
+        match type_environment.resolve(&type_environment.generalize(type_, in_signature)) {
+            Ok(type_) => type_,
+            Err(ResolveErr::UndefinedType(name)) => {
+                errs.push(ParserErr::UndefinedType { name, span });
+                Type::Undefined
+            }
+            Err(ResolveErr::TypeArgCount { name, expected, found }) => {
+                errs.push(ParserErr::TypeArgCount { name, expected, found, span });
+                Type::Undefined
+            }
+        }
+    }
+
+    /**
+     * Infers the type of an expression, checking it along the way. The expected type, if known,
+     * gives unannotated function parameters their types
+     **/
+    fn predict_type(&self, expr: &Expression, expected: Option<&Type>, type_environment: &mut TypeEnvironment, errs: &mut Vec<ParserErr>) -> Access<Type> {
+        match expr {
+            Expression::MathExpr(expr) => self.type_math_expr(expr, type_environment, errs),
+            Expression::StrLiteral(_) => Access::Owned(Type::Primitive(PrimitiveType::Str)),
+            Expression::CharLiteral(_) => Access::Owned(Type::Primitive(PrimitiveType::Char)),
+            Expression::Compound(exprs) => {
+                let expected = match expected.map(Type::unalias) {
+                    Some(Type::Compound(types)) if types.len() == exprs.len() => types.iter().map(|t| Some(t.map(Clone::clone))).collect(),
+                    _ => vec![None; exprs.len()],
+                };
+
+                exprs.iter()
+                    .zip(expected)
+                    .map(|(expr, expected)| self.predict_type(expr, expected.as_ref(), type_environment, errs))
+                    .collect::<Type>()
+                    .into()
+            }
+            Expression::FuncLiteral { params, statements, span, end, .. } => {
+                type_environment.symbols_mut().enter_scope(*span, *end);
+                let type_ = self.type_function(params, statements, expected, type_environment, errs);
+                type_environment.symbols_mut().exit_scope();
+                type_environment.symbols_mut().record_type(*span, type_.map(Clone::clone));
+                type_
+            }
+        }
+    }
+
+    fn type_function(&self, params: &[Identifier], statements: &[AstNode], expected: Option<&Type>, type_environment: &mut TypeEnvironment, errs: &mut Vec<ParserErr>) -> Access<Type> {
+        let expected_params = match expected.map(Type::unalias) {
+            Some(Type::Function(types, _, _)) if types.len() == params.len() => Some(types),
+            _ => None,
+        };
+        let declared_pure = matches!(expected.map(Type::unalias), Some(Type::Function(_, _, Effect::Pure)));
+
+        type_environment.push_scope();
+
+        let mut param_types = vec![];
+        for (i, param) in params.iter().enumerate() {
+            let Identifier::Identifier(name, t, span) = param else {
+                continue;
+            };
+
+            let type_ = match (t.as_ref(), expected_params) {
+                (Type::Undefined, Some(types)) => types[i].map(Clone::clone),
+                _ => self.resolve_type(t, true, *span, type_environment, errs),
+            };
+
+            type_environment.declare(name, SymbolKind::Parameter, Access::Owned(type_.clone()), false, *span);
+            param_types.push(Access::Owned(type_));
+        }
+
+        type_environment.push_function(declared_pure);
+        let return_type = self.type_statements(statements, type_environment, errs);
+        let function = type_environment.pop_function();
+        type_environment.pop_scope();
+
+        let return_type = self.check_propagated(return_type.map(Clone::clone), function.propagated, errs);
+        let effect = if function.impure { Effect::Impure } else { Effect::Pure };
+        Access::Owned(Type::Function(param_types, Box::new(return_type), effect))
+    }
+
+    /**
+     * Records an impure call in the enclosing function, which must not be declared pure
+     **/
+    fn perform_impure(&self, span: Span, type_environment: &mut TypeEnvironment, errs: &mut Vec<ParserErr>) {
+        if !type_environment.perform_impure() {
+            errs.push(ParserErr::ImpureCall { span });
+        }
+    }
+
+    /**
+     * Checks the error types propagated with `?` against the function's returned Result,
+     * returning the function's return type with the error type filled in
+     **/
+    fn check_propagated(&self, return_type: Type, propagated: Vec<(Type, Span)>, errs: &mut Vec<ParserErr>) -> Type {
+        if propagated.is_empty() {
+            return return_type;
+        }
+
+        let (value, mut err) = match return_type.unalias() {
+            Type::Result(value, err) => (value.as_ref().clone(), err.as_ref().clone()),
+            // a function which only propagates errors returns a Result of nothing
+            Type::Unit => (Type::Unit, Type::Undefined),
+            Type::Undefined => (Type::Undefined, Type::Undefined),
+            _ => {
+                for (_, span) in propagated {
+                    errs.push(ParserErr::InvalidPropagation {
+                        type_: Access::Owned(return_type.clone()),
+                        span,
+                    });
+                }
+
+                return return_type;
+            }
+        };
+
+        for (found, span) in propagated {
+            match err.merge(&found) {
+                Some(merged) => err = merged,
+                None => errs.push(ParserErr::PropagatedErrorType {
+                    expected: Access::Owned(err.clone()),
+                    found: Access::Owned(found),
+                    span,
+                }),
+            }
+        }
+
+        Type::Result(Box::new(value), Box::new(err))
+    }
+
+    fn type_ident(&self, ident: &Identifier, type_environment: &mut TypeEnvironment, errs: &mut Vec<ParserErr>) -> Access<Type> {
+        match ident {
+            Identifier::Identifier(name, t, span) => match **t {
+                Type::Undefined => {
+                    let Some(binding) = type_environment.lookup(&name.into()).cloned() else {
+                        type_environment.use_unbound(name, *span);
+                        return Access::Owned(Type::Undefined);
+                    };
+
+                    if let Some(symbol) = binding.symbol {
+                        type_environment.symbols_mut().reference(*span, symbol);
+                    }
+
+                    type_environment.symbols_mut().record_type(*span, binding.type_.map(Clone::clone));
+                    binding.type_
+                }
+                _ => Access::Rc(t.clone()),
+            },
+            Identifier::Compound(vec) => vec.iter()
+                .map(|id| self.type_ident(id, type_environment, errs))
+                .collect::<Type>()
+                .into(),
+            Identifier::Mutable(ident) => self.type_ident(ident, type_environment, errs),
+            Identifier::Accessor(receiver, member) => {
+                let receiver_type = self.type_ident(receiver, type_environment, errs);
+                if receiver_type.map(|t| matches!(t.unalias(), Type::Optional(_))) {
+                    errs.push(ParserErr::UnwrappedOptional {
+                        type_: receiver_type,
+                        span: receiver.span(),
+                    });
+                    return Access::Owned(Type::Undefined);
+                }
+
+                self.type_access(receiver_type, member, type_environment, errs)
+            }
+            Identifier::SafeAccessor(receiver, member) => {
+                // accessing through none gives none, so the result is optional as well
+                let receiver_type = self.type_ident(receiver, type_environment, errs);
+                let receiver_type = match receiver_type.map(|t| t.unalias().clone()) {
+                    Type::Optional(inner) => Access::Owned(*inner),
+                    _ => receiver_type,
+                };
+
+                match self.type_access(receiver_type, member, type_environment, errs).map(|t| t.unalias().clone()) {
+                    Type::Undefined => Access::Owned(Type::Undefined),
+                    Type::Optional(inner) => Access::Owned(Type::Optional(inner)),
+                    type_ => Access::Owned(Type::Optional(Box::new(type_))),
+                }
+            }
+        }
+    }
+
+    fn type_access(&self, receiver: Access<Type>, member: &Identifier, type_environment: &mut TypeEnvironment, errs: &mut Vec<ParserErr>) -> Access<Type> {
+        let Identifier::Identifier(name, _, span) = member else {
+            return Access::Owned(Type::Undefined);
+        };
+
+        if receiver.map(|t| matches!(t.unalias(), Type::Undefined | Type::Param(_))) {
+            return Access::Owned(Type::Undefined);
+        }
+
+        let type_ = self.type_member(receiver, name, *span, type_environment, errs);
+        type_environment.symbols_mut().record_type(*span, type_.map(Clone::clone));
+        type_
+    }
+
+    fn type_member(&self, receiver: Access<Type>, name: &str, span: Span, type_environment: &mut TypeEnvironment, errs: &mut Vec<ParserErr>) -> Access<Type> {
+        let method = receiver.map(|t| type_environment.lookup_method(t, name).cloned());
+        if let Some((_, symbol)) = method {
+            type_environment.symbols_mut().reference(span, symbol);
+        }
+
+        match method.map(|(type_, _)| type_) {
+            // the receiver is the first argument of the method
+            Some(Type::Function(params, return_type, effect)) if params.len() <= 1 => {
+                // accessing a method without other parameters calls it
+                if effect == Effect::Impure {
+                    self.perform_impure(span, type_environment, errs);
+                }
+
+                Access::Owned(*return_type)
+            }
+            Some(Type::Function(params, return_type, effect)) => Access::Owned(Type::Function(params[1..].to_vec(), return_type, effect)),
+            Some(type_) => Access::Owned(type_),
+            None => {
+                errs.push(if type_environment.is_method(name) {
+                    ParserErr::MissingImpl {
+                        method: name.to_string(),
+                        type_: receiver,
+                        span,
+                    }
+                } else {
+                    ParserErr::UnknownField {
+                        name: name.to_string(),
+                        type_: receiver,
+                        span,
+                    }
+                });
+
+                Access::Owned(Type::Undefined)
+            }
+        }
+    }
+
+    fn type_math_expr(&self, expr: &MathExpr, type_environment: &mut TypeEnvironment, errs: &mut Vec<ParserErr>) -> Access<Type> {
+        let type_ = self.infer_math_expr(expr, type_environment, errs);
+        type_environment.symbols_mut().record_type(expr.span(), type_.map(Clone::clone));
+        type_
+    }
+
+    fn infer_math_expr(&self, expr: &MathExpr, type_environment: &mut TypeEnvironment, errs: &mut Vec<ParserErr>) -> Access<Type> {
+        match expr {
+            MathExpr::Literal(literal, _) => Access::Owned(Type::Primitive(literal.prim_type())),
+            MathExpr::Identifier(ident) => self.type_ident(ident, type_environment, errs),
+            MathExpr::BinaryOp(lhs, rhs, _) => {
+                let lhs_type = self.type_math_expr(lhs, type_environment, errs);
+                let rhs_type = self.type_math_expr(rhs, type_environment, errs);
+
+                let lhs_valid = self.check_operand(lhs, &lhs_type, errs);
+                let rhs_valid = self.check_operand(rhs, &rhs_type, errs);
+                if !lhs_valid || !rhs_valid {
+                    return Access::Owned(Type::Undefined);
+                }
+
+                self.operation_type(lhs, lhs_type, rhs, rhs_type, expr.span(), errs)
+            }
+            MathExpr::Negate(expr) => {
+                let type_ = self.type_math_expr(expr, type_environment, errs);
+                if !self.check_operand(expr, &type_, errs) {
+                    return Access::Owned(Type::Undefined);
+                }
+
+                type_
+            }
+            MathExpr::FuncCall(ident, args) => self.type_call(ident, args, type_environment, errs),
+            MathExpr::Cast(expr, to, span) => {
+                let from = self.type_math_expr(expr, type_environment, errs);
+                let to = self.resolve_type(to, false, *span, type_environment, errs);
+                if !from.map(|from| from.castable_to(&to)) {
+                    errs.push(ParserErr::InvalidCast {
+                        from,
+                        to: Access::Owned(to.clone()),
+                        span: *span,
+                    });
+                }
+
+                Access::Owned(to)
+            }
+            MathExpr::None(_) => Access::Owned(Type::Optional(Box::new(Type::Undefined))),
+            MathExpr::Try(expr, span) => {
+                let type_ = self.type_math_expr(expr, type_environment, errs);
+                let (value, err) = match type_.map(|t| t.unalias().clone()) {
+                    Type::Result(value, err) => (*value, *err),
+                    Type::Undefined => (Type::Undefined, Type::Undefined),
+                    _ => {
+                        errs.push(ParserErr::NotResult {
+                            type_,
+                            span: *span,
+                        });
+                        return Access::Owned(Type::Undefined);
+                    }
+                };
+
+                if !type_environment.propagate(err, *span) {
+                    errs.push(ParserErr::PropagationOutsideFunction { span: *span });
+                }
+
+                Access::Owned(value)
+            }
+            MathExpr::Coalesce(lhs, rhs) => {
+                let lhs_type = self.type_math_expr(lhs, type_environment, errs);
+                let rhs_type = self.predict_type(rhs, None, type_environment, errs);
+
+                let inner = match lhs_type.map(|t| t.unalias().clone()) {
+                    Type::Optional(inner) => *inner,
+                    // coalescing a value which can not be none just gives the value
+                    _ => return lhs_type,
+                };
+
+                if inner == Type::Undefined {
+                    return rhs_type;
+                }
+
+                // the fallback may be a narrower literal, like the operands of arithmetic
+                if !rhs_type.map(|t| self.value_assignable(rhs, t, &inner)) {
+                    errs.push(ParserErr::UnmatchedTypes {
+                        type_1: rhs_type,
+                        type_2: Access::Owned(inner.clone()),
+                        span: expr.span(),
+                    });
+                }
+
+                Access::Owned(inner)
+            }
+        }
+    }
+
+    /**
+     * The type of a binary operation, that of the operand the other one widens to. A literal operand takes
+     * the type of the other operand if it fits in it, operands which do not widen either way need a cast
+     **/
+    fn operation_type(&self, lhs: &MathExpr, lhs_type: Access<Type>, rhs: &MathExpr, rhs_type: Access<Type>, span: Span, errs: &mut Vec<ParserErr>) -> Access<Type> {
+        if lhs_type.map(|t| t == &Type::Undefined) || rhs_type.map(|t| t == &Type::Undefined) {
+            // already reported where the unknown operand came from
+            return Access::Owned(Type::Undefined);
+        }
+
+        let widens = |from: &Access<Type>, to: &Access<Type>| from.map(|from| to.map(|to| match (from.unalias(), to.unalias()) {
+            (Type::Primitive(from), Type::Primitive(to)) => from.widens_to(to),
+            (from, to) => from == to,
+        }));
+
+        // a literal adapts before widening, its own type is only a default
+        if rhs_type.map(|t| self.literal_fits(lhs, t)) {
+            rhs_type
+        } else if lhs_type.map(|t| self.literal_fits(rhs, t)) {
+            lhs_type
+        } else if widens(&lhs_type, &rhs_type) {
+            rhs_type
+        } else if widens(&rhs_type, &lhs_type) {
+            lhs_type
+        } else {
+            errs.push(ParserErr::IncompatibleOperands {
+                lhs: lhs_type,
+                rhs: rhs_type,
+                span,
+            });
+            Access::Owned(Type::Undefined)
+        }
+    }
+
+    /**
+     * Reports an operand which can not be used as a plain value, an optional or the result of
+     * a call returning Unit. Returns false if it was one
+     **/
+    fn check_operand(&self, expr: &MathExpr, type_: &Access<Type>, errs: &mut Vec<ParserErr>) -> bool {
+        match type_.map(|t| t.unalias().clone()) {
+            Type::Optional(_) => errs.push(ParserErr::UnwrappedOptional {
+                type_: type_.clone(),
+                span: expr.span(),
+            }),
+            Type::Unit => errs.push(ParserErr::UnitValue { span: expr.span() }),
+            _ => return true,
+        }
+
+        false
+    }
+
+    /**
+     * Checks the arguments of a call against the callee's signature, instantiating its type parameters
+     * from the arguments. Returns the type the call evaluates to
+     **/
+    fn type_call(&self, ident: &Identifier, args: &[Expression], type_environment: &mut TypeEnvironment, errs: &mut Vec<ParserErr>) -> Access<Type> {
+        let callee = self.type_ident(ident, type_environment, errs);
+        let (params, return_type) = match callee.map(|t| t.unalias().clone()) {
+            Type::Function(params, return_type, effect) => {
+                if effect == Effect::Impure {
+                    self.perform_impure(ident.span(), type_environment, errs);
+                }
+
+                (params, return_type)
+            }
+            Type::Undefined => {
+                for arg in args {
+                    self.predict_type(arg, None, type_environment, errs);
                 }
+
+                return Access::Owned(Type::Undefined);
+            }
+            _ => {
+                errs.push(ParserErr::NotCallable {
+                    type_: callee,
+                    span: ident.span(),
+                });
+                return Access::Owned(Type::Undefined);
+            }
+        };
+
+        if params.len() != args.len() {
+            errs.push(ParserErr::ArgumentCount {
+                expected: params.len(),
+                found: args.len(),
+                span: ident.span(),
+            });
+        }
+
+        let mut type_args = HashMap::new();
+        for (param, arg) in params.iter().zip(args) {
+            let param = param.map(|t| t.substitute(&type_args));
+            let arg_type = self.predict_type(arg, Some(&param), type_environment, errs);
+            self.check_literal_ranges(arg, &param, errs);
+
+            if !arg_type.map(|t| param.infer_params(t, &mut type_args) || self.value_assignable(arg, t, &param)) {
+                errs.push(ParserErr::UnmatchedArgument {
+                    expected: Access::Owned(param),
+                    found: arg_type,
+                    span: match arg {
+                        Expression::MathExpr(expr) => expr.span(),
+                        _ => ident.span(),
+                    },
+                });
+            }
+        }
+
+        // parameters can be bound to other parameters, e.g. when passing a generic function
+        let mut return_type = *return_type;
+        for _ in 0..type_args.len() {
+            return_type = return_type.substitute(&type_args);
+        }
+
+        Access::Owned(return_type)
+    }
+
+    fn check_literal_ranges(&self, expr: &Expression, expected: &Type, errs: &mut Vec<ParserErr>) {
+        match (expr, expected.unalias()) {
+            (Expression::MathExpr(expr), Type::Primitive(prim)) => self.check_math_literal_ranges(expr, prim, false, errs),
+            (expr, Type::Optional(type_)) => self.check_literal_ranges(expr, type_, errs),
+            (Expression::Compound(exprs), Type::Compound(types)) => {
+                for (expr, type_) in exprs.iter().zip(types) {
+                    type_.map(|t| self.check_literal_ranges(expr, t, errs));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn check_math_literal_ranges(&self, expr: &MathExpr, expected: &PrimitiveType, negated: bool, errs: &mut Vec<ParserErr>) {
+        match expr {
+            MathExpr::Literal(num, span) => {
+                let sign = if negated { "-" } else { "" };
+
+                let (fits, min, max) = if let Some((min, max)) = expected.int_range() {
+                    // a floating point literal in an integer is a type mismatch, not a range error.
+                    // literals themselves are never negative, a minus sign negates them
+                    let Some(value) = num.as_uint() else { return };
+                    let fits = if negated { value <= min.unsigned_abs() } else { value <= max };
+                    (fits, min.to_string(), max.to_string())
+                } else if let Some((min, max)) = expected.float_range() {
+                    let value = if negated { -num.as_float() } else { num.as_float() };
+                    (value.is_finite() && value >= min && value <= max, format!("{:e}", min), format!("{:e}", max))
+                } else {
+                    return;
+                };
+
+                if !fits {
+                    errs.push(ParserErr::LiteralOutOfRange {
+                        literal: format!("{}{}", sign, num),
+                        type_: expected.clone(),
+                        min,
+                        max,
+                        span: *span,
+                    });
+                }
+            }
+            MathExpr::Negate(expr) => self.check_math_literal_ranges(expr, expected, !negated, errs),
+            MathExpr::BinaryOp(lhs, rhs, _) => {
+                self.check_math_literal_ranges(lhs, expected, false, errs);
+                self.check_math_literal_ranges(rhs, expected, false, errs);
             }
-            _ => todo!()
+            // literals being cast are converted rather than range checked
+            _ => {}
         }
     }
 }
\ No newline at end of file
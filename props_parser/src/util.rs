@@ -1,8 +1,9 @@
 use std::cmp::Ordering;
-use std::fmt::{Debug, Display, format, Formatter};
+use std::fmt::{Display, Formatter};
+use std::hash::{Hash, Hasher};
 use std::rc::Rc;
 
-#[derive(Debug, Hash, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone)]
 pub enum Access<T> {
     Rc(Rc<T>),
     Owned(T)
@@ -26,6 +27,21 @@ impl <T> From<T> for Access<T> {
     }
 }
 
+// compare the values rather than how they are held, so an owned key finds a shared one
+impl<T: PartialEq> PartialEq for Access<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.map(|v1| other.map(|v2| v1 == v2))
+    }
+}
+
+impl<T: Eq> Eq for Access<T> {}
+
+impl<T: Hash> Hash for Access<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.map(|v| v.hash(state))
+    }
+}
+
 impl<T: PartialOrd> PartialOrd for Access<T> {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         match (self, other) {
@@ -38,7 +54,7 @@ impl<T: PartialOrd> PartialOrd for Access<T> {
 }
 
 impl<T> Access<T> {
-    pub fn map<R, F: Fn(&T) -> R>(&self, f: F) -> R {
+    pub fn map<R, F: FnOnce(&T) -> R>(&self, f: F) -> R {
         match self {
             Access::Rc(rc) => f(rc.as_ref()),
             Access::Owned(v) => f(v)
@@ -46,11 +62,8 @@ impl<T> Access<T> {
     }
 }
 
-impl<T: Debug> Display for Access<T> {
+impl<T: Display> Display for Access<T> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Access::Rc(rc) => write!(f, "{:?}", rc),
-            Access::Owned(v) => write!(f, "{:?}", v),
-        }
+        self.map(|v| v.fmt(f))
     }
 }
\ No newline at end of file
@@ -0,0 +1,50 @@
+mod common;
+
+use props_parser::error::ParserErr;
+use props_parser::types::{PrimitiveType, Type};
+use PrimitiveType::*;
+use common::errs;
+
+fn alias(name: &str, type_: Type) -> Type {
+    Type::Alias(name.to_string(), vec![], Box::new(type_))
+}
+
+#[test]
+fn alias_equals_its_type() {
+    let meters = alias("Meters", Type::Primitive(F64));
+
+    assert_eq!(meters, Type::Primitive(F64));
+    assert_eq!(meters, alias("Distance", Type::Primitive(F64)));
+    assert_eq!(alias("Length", meters.clone()), Type::Primitive(F64));
+    assert_ne!(meters, Type::Primitive(F32));
+}
+
+#[test]
+fn alias_used_interchangeably() {
+    assert!(errs("type Meters = F64\nm: Meters = 1.5\nx: F64 = m\ny: Meters = x\n").is_empty());
+    assert!(errs("type Pair = (I32, Str)\np: Pair = 1, \"s\"\nq: (I32, Str) = p\na, b = p\n").is_empty());
+    assert!(errs("type Meters = F64\ntype Distance = Meters\nd: Distance = 1.5\nm: Meters = d\n").is_empty());
+}
+
+#[test]
+fn alias_displays_its_name() {
+    assert_eq!(alias("Meters", Type::Primitive(F64)).to_string(), "Meters");
+
+    let errs = errs("type Pair = (I32, Str)\np: Pair = 1, \"s\"\ns: Str = p\n");
+    assert!(matches!(errs.as_slice(), [ParserErr::UnmatchedTypes { .. }]), "{:?}", errs);
+    assert_eq!(errs[0].to_string(), "Can not assign type Pair to an identifier of type Str");
+}
+
+#[test]
+fn alias_checked_structurally() {
+    let errs = errs("type Pair = (I32, Str)\np: Pair = \"s\", 1\n");
+
+    assert!(matches!(errs.as_slice(), [ParserErr::UnmatchedTypes { .. }]), "{:?}", errs);
+}
+
+#[test]
+fn undefined_type() {
+    let errs = errs("m: Meters = 1.5\n");
+
+    assert!(matches!(errs.as_slice(), [ParserErr::UndefinedType { .. }]), "{:?}", errs);
+}
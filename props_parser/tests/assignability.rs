@@ -0,0 +1,111 @@
+mod common;
+
+use props_parser::error::ParserErr;
+use props_parser::types::{PrimitiveType, Type};
+use strum::IntoEnumIterator;
+use PrimitiveType::*;
+use common::errs;
+
+/** Every primitive a type widens to, besides itself **/
+fn widenings(from: &PrimitiveType) -> Vec<PrimitiveType> {
+    match from {
+        U8 => vec![U16, U32, U64, U128, I16, I32, I64, I128, F32, F64, BigInt, Decimal],
+        U16 => vec![U32, U64, U128, I32, I64, I128, F32, F64, BigInt, Decimal],
+        U32 => vec![U64, U128, I64, I128, F64, BigInt, Decimal],
+        U64 => vec![U128, I128, BigInt, Decimal],
+        U128 => vec![BigInt],
+        I8 => vec![I16, I32, I64, I128, F32, F64, BigInt, Decimal],
+        I16 => vec![I32, I64, I128, F32, F64, BigInt, Decimal],
+        I32 => vec![I64, I128, F64, BigInt, Decimal],
+        I64 => vec![I128, BigInt, Decimal],
+        I128 => vec![BigInt],
+        F32 => vec![F64],
+        F64 | BigInt | Decimal | Char | Str => vec![],
+    }
+}
+
+#[test]
+fn primitive_pairs() {
+    for from in PrimitiveType::iter() {
+        for to in PrimitiveType::iter() {
+            let expected = from == to || widenings(&from).contains(&to);
+            let actual = Type::Primitive(from.clone()).assignable_to(&Type::Primitive(to.clone()));
+
+            assert_eq!(actual, expected, "{} assignable to {}", from, to);
+        }
+    }
+}
+
+#[test]
+fn primitive_pairs_through_bindings() {
+    for from in PrimitiveType::iter() {
+        for to in PrimitiveType::iter() {
+            let source = format!("f = |a: {}| {{\n\tb: {} = a\n}}\n", from, to);
+            let errs = errs(&source);
+            let expected = from == to || widenings(&from).contains(&to);
+
+            assert_eq!(errs.is_empty(), expected, "{} assigned to {}: {:?}", from, to, errs);
+        }
+    }
+}
+
+#[test]
+fn optional_accepts_value() {
+    let optional = |t| Type::Optional(Box::new(Type::Primitive(t)));
+
+    assert!(Type::Primitive(U8).assignable_to(&optional(I32)));
+    assert!(optional(U8).assignable_to(&optional(I32)));
+    assert!(!optional(I32).assignable_to(&Type::Primitive(I32)));
+}
+
+#[test]
+fn matching_annotation() {
+    assert!(errs("x: I32 = 5\ny: I32 = x\n").is_empty());
+    assert!(errs("s: Str = \"s\"\n").is_empty());
+}
+
+#[test]
+fn literal_takes_annotated_type() {
+    assert!(errs("x: U8 = 5\ny: F64 = 3\nz: I64 = -2\n").is_empty());
+}
+
+#[test]
+fn literal_out_of_range() {
+    let errs = errs("x: U8 = 300\n");
+
+    assert!(matches!(errs.as_slice(), [ParserErr::LiteralOutOfRange { .. }]), "{:?}", errs);
+}
+
+#[test]
+fn large_float_literal_parses_as_f64() {
+    let literal = format!("1{}.0", "0".repeat(48));
+
+    assert!(errs(&format!("x: F64 = {}\n", literal)).is_empty());
+
+    let errs = errs(&format!("x: F32 = -{}\n", literal));
+    assert!(matches!(errs.as_slice(), [ParserErr::LiteralOutOfRange { .. }]), "{:?}", errs);
+    assert!(errs[0].to_string().starts_with(&format!("Literal -{} does not fit", literal)), "{}", errs[0]);
+}
+
+#[test]
+fn out_of_range_literal_reported_as_written() {
+    let literal = format!("1{}.5", "0".repeat(400));
+    let errs = errs(&format!("x: F64 = {}\n", literal));
+
+    assert!(matches!(errs.as_slice(), [ParserErr::LiteralOutOfRange { .. }]), "{:?}", errs);
+    assert!(errs[0].to_string().starts_with(&format!("Literal {} does not fit", literal)), "{}", errs[0]);
+}
+
+#[test]
+fn unmatched_annotation() {
+    let errs = errs("x: I32 = \"s\"\n");
+
+    assert!(matches!(errs.as_slice(), [ParserErr::UnmatchedTypes { .. }]), "{:?}", errs);
+}
+
+#[test]
+fn narrowing_reassignment() {
+    let errs = errs("mut x: I16 = 1\ny: I32 = 2\nx = y\n");
+
+    assert!(matches!(errs.as_slice(), [ParserErr::ReassignedType { .. }]), "{:?}", errs);
+}
@@ -0,0 +1,86 @@
+mod common;
+
+use num_bigint::BigInt;
+use props_parser::error::ParserErr;
+use props_parser::nodes::{AstNode, Expression, MathExpr};
+use props_parser::pipeline::PropsPipeline;
+use props_parser::types::{Number, PrimitiveType};
+use rust_decimal::Decimal;
+use std::str::FromStr;
+use common::errs;
+
+#[test]
+fn operands_without_common_type_need_a_cast() {
+    for source in [
+        "a: U64 = 1\nb: I64 = 2\nc: Str = a + b\n",
+        "a: F32 = 1.5\nb: I32 = 2\nc: Str = a + b\n",
+    ] {
+        let errs = errs(source);
+        assert!(matches!(errs.as_slice(), [ParserErr::IncompatibleOperands { .. }]), "{}: {:?}", source, errs);
+    }
+}
+
+#[test]
+fn cast_operand_has_common_type() {
+    let errs = errs("a: U64 = 1\nb: I64 = 2\nc: I64 = a as I64 + b\n");
+    assert!(errs.is_empty(), "{:?}", errs);
+}
+
+#[test]
+fn unknown_operand_reported_once() {
+    let errs = errs("a: I32 = 1\nc = a + b\n");
+    assert!(matches!(errs.as_slice(), [ParserErr::UndefinedBinding { .. }]), "{:?}", errs);
+}
+
+#[test]
+fn narrowing_wraps() {
+    assert_eq!(Number::I32(300).cast(&PrimitiveType::U8), Some(Number::U8(44)));
+    assert_eq!(Number::I16(-1).cast(&PrimitiveType::U16), Some(Number::U16(u16::MAX)));
+    assert_eq!(Number::U128(u128::MAX).cast(&PrimitiveType::I8), Some(Number::I8(-1)));
+}
+
+#[test]
+fn float_to_int_saturates() {
+    assert_eq!(Number::F64(1e10).cast(&PrimitiveType::I32), Some(Number::I32(i32::MAX)));
+    assert_eq!(Number::F32(-5.7).cast(&PrimitiveType::U8), Some(Number::U8(0)));
+    assert_eq!(Number::F64(-5.7).cast(&PrimitiveType::I64), Some(Number::I64(-5)));
+    assert_eq!(Number::F64(f64::NAN).cast(&PrimitiveType::I32), Some(Number::I32(0)));
+}
+
+#[test]
+fn decimal_truncates() {
+    let decimal = |s| Number::Decimal(Decimal::from_str(s).unwrap());
+
+    assert_eq!(decimal("2.9").cast(&PrimitiveType::I32), Some(Number::I32(2)));
+    assert_eq!(decimal("-2.9").cast(&PrimitiveType::I32), Some(Number::I32(-2)));
+    assert_eq!(decimal("-2.9").cast(&PrimitiveType::BigInt), Some(Number::BigInt(BigInt::from(-2))));
+}
+
+#[test]
+fn bigint_wraps() {
+    let big: BigInt = BigInt::from(1u8) << 130;
+
+    assert_eq!(Number::BigInt(big.clone() + 5).cast(&PrimitiveType::U8), Some(Number::U8(5)));
+    assert_eq!(Number::BigInt(big - 1).cast(&PrimitiveType::I64), Some(Number::I64(-1)));
+    assert_eq!(Number::BigInt(BigInt::from(-1)).cast(&PrimitiveType::U128), Some(Number::U128(u128::MAX)));
+}
+
+#[test]
+fn cast_literal_is_not_range_checked() {
+    let errs = errs("x: U8 = 300 as U8\n");
+    assert!(errs.is_empty(), "{:?}", errs);
+}
+
+#[test]
+fn negation_is_cast_rather_than_negating_cast() {
+    let (ast, parse_errs) = PropsPipeline::default().parse("x = -1 as U8\n".to_string());
+    assert!(parse_errs.is_empty(), "{:?}", parse_errs);
+
+    let [AstNode::Assignment(_, Expression::MathExpr(MathExpr::Cast(expr, ..)))] = ast.as_slice() else {
+        panic!("expected a cast: {:?}", ast);
+    };
+    assert!(matches!(expr.as_ref(), MathExpr::Negate(_)), "{:?}", expr);
+
+    let errs = errs("a: I32 = 1\nb: U8 = -a as U8\nc: I32 = -(a as U8) as I32\n");
+    assert!(errs.is_empty(), "{:?}", errs);
+}
@@ -0,0 +1,17 @@
+// each test file uses only some of the helpers
+#![allow(dead_code)]
+
+use props_parser::error::ParserErr;
+use props_parser::pipeline::PropsPipeline;
+
+pub fn errs(source: &str) -> Vec<ParserErr> {
+    PropsPipeline::default().parse(source.to_string()).1
+}
+
+/** The type of the last binding with a name, as written in source **/
+pub fn type_of(source: &str, name: &str) -> String {
+    let mut pipeline = PropsPipeline::default();
+    pipeline.parse(source.to_string());
+
+    pipeline.symbols().symbols().iter().rev().find(|symbol| symbol.name == name).unwrap().type_.to_string()
+}
@@ -0,0 +1,62 @@
+mod common;
+
+use props_parser::error::ParserErr;
+use props_parser::pipeline::PropsPipeline;
+use common::errs;
+
+/** The types of the bindings of a source, as written in source **/
+fn types(source: &str) -> Vec<(String, String)> {
+    let mut pipeline = PropsPipeline::default();
+    let (_, errs) = pipeline.parse(source.to_string());
+    assert!(errs.is_empty(), "{:?}", errs);
+
+    pipeline.symbols().symbols().iter().map(|symbol| (symbol.name.clone(), symbol.type_.to_string())).collect()
+}
+
+fn pairs(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+    pairs.iter().map(|(name, type_)| (name.to_string(), type_.to_string())).collect()
+}
+
+#[test]
+fn each_identifier_takes_its_element_type() {
+    assert_eq!(types("a, b = 1, \"s\"\n"), pairs(&[("a", "I16"), ("b", "Str")]));
+    assert_eq!(types("t = 1, 2.5\nx, y = t\n"), pairs(&[("t", "(I16, F32)"), ("x", "I16"), ("y", "F32")]));
+}
+
+#[test]
+fn nested_destructuring() {
+    assert_eq!(
+        types("(a, b), c = (1, 2), 3\n"),
+        pairs(&[("a", "I16"), ("b", "I16"), ("c", "I16")]),
+    );
+}
+
+#[test]
+fn unmatched_arity() {
+    for source in ["a, b = 1, 2, 3\n", "t = 1, 2\na, b, c = t\n", "(a, b), c = (1, 2, 3), 4\n"] {
+        let errs = errs(source);
+        assert!(matches!(errs.as_slice(), [ParserErr::UnmatchedArity { .. }]), "{}: {:?}", source, errs);
+    }
+}
+
+#[test]
+fn not_compound() {
+    for source in ["x: I32 = 1\na, b = x\n", "(a, b), c = 1, 2\n"] {
+        let errs = errs(source);
+        assert!(matches!(errs.as_slice(), [ParserErr::NotCompound { .. }]), "{}: {:?}", source, errs);
+    }
+}
+
+#[test]
+fn literals_checked_against_each_annotation() {
+    assert!(errs("a: U8, b: I64 = 255, -3\n").is_empty());
+
+    let errs = errs("a: U8, b: I8 = 1, 300\n");
+    assert!(matches!(errs.as_slice(), [ParserErr::LiteralOutOfRange { .. }]), "{:?}", errs);
+}
+
+#[test]
+fn value_checked_against_each_annotation() {
+    let errs = errs("a: I32, b: Str = 1, 2\n");
+    assert!(matches!(errs.as_slice(), [ParserErr::UnmatchedTypes { .. }]), "{:?}", errs);
+}
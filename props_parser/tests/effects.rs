@@ -0,0 +1,46 @@
+mod common;
+
+use props_parser::error::ParserErr;
+use common::{errs, type_of};
+
+const LOG: &str = "log = |s: I32| {\n\treturn s\n}\n";
+
+#[test]
+fn impure_call_makes_function_impure() {
+    let source = format!("{}f = |a: I32| {{\n\tlog a\n\treturn a\n}}\ng = |a: I32| a\n", LOG);
+
+    assert!(errs(&source).is_empty());
+    assert_eq!(type_of(&source, "f"), "impure (I32) -> I32");
+    assert_eq!(type_of(&source, "g"), "(I32) -> I32");
+}
+
+#[test]
+fn calling_impure_function_is_impure() {
+    let source = format!("{}f = |a: I32| {{\n\tlog a\n\treturn a\n}}\ng = |a: I32| f a\n", LOG);
+
+    assert_eq!(type_of(&source, "g"), "impure (I32) -> I32");
+}
+
+#[test]
+fn impure_call() {
+    let source = format!("{}f: (I32) -> I32 = |a| {{\n\tlog a\n\treturn a\n}}\n", LOG);
+    let errs = errs(&source);
+
+    // only the call is reported, the function keeps its declared effect
+    assert!(matches!(errs.as_slice(), [ParserErr::ImpureCall { .. }]), "{:?}", errs);
+    assert_eq!(type_of(&source, "f"), "(I32) -> I32");
+}
+
+#[test]
+fn impure_function_not_assignable_to_pure() {
+    let source = format!("{}f = |a: I32| {{\n\tlog a\n\treturn a\n}}\ng: (I32) -> I32 = f\nh: impure (I32) -> I32 = f\n", LOG);
+    let errs = errs(&source);
+
+    assert!(matches!(errs.as_slice(), [ParserErr::UnmatchedTypes { .. }]), "{:?}", errs);
+    assert_eq!(errs[0].to_string(), "Can not assign type impure (I32) -> I32 to an identifier of type (I32) -> I32");
+}
+
+#[test]
+fn pure_function_assignable_to_impure() {
+    assert!(errs("g = |a: I32| a\nh: impure (I32) -> I32 = g\n").is_empty());
+}
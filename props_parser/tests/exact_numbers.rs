@@ -0,0 +1,77 @@
+mod common;
+
+use num_bigint::BigInt;
+use props_parser::error::ParserErr;
+use props_parser::nodes::MathOp;
+use props_parser::types::Number;
+use rust_decimal::Decimal;
+use std::str::FromStr;
+use common::errs;
+
+#[test]
+fn exact_types_do_not_mix_with_floats() {
+    for source in [
+        "a: BigInt = 1n\nb: F64 = 1.5\nc: Str = a + b\n",
+        "a: F32 = 1.5\nb: Decimal = 1d\nc = a * b\n",
+    ] {
+        let errs = errs(source);
+        assert!(matches!(errs.as_slice(), [ParserErr::IncompatibleOperands { .. }]), "{}: {:?}", source, errs);
+    }
+}
+
+#[test]
+fn bigint_and_decimal_do_not_mix() {
+    let errs = errs("a: BigInt = 1n\nb: Decimal = 1d\nc: Str = a + b\n");
+
+    assert!(matches!(errs.as_slice(), [ParserErr::IncompatibleOperands { .. }]), "{:?}", errs);
+}
+
+#[test]
+fn integers_widen_to_exact_types() {
+    assert!(errs("a: BigInt = 1n\nb: I64 = 2\nc: BigInt = a + b\n").is_empty());
+    assert!(errs("a: Decimal = 1d\nb: I32 = 2\nc: Decimal = b * a\n").is_empty());
+}
+
+fn decimal(s: &str) -> Number {
+    Number::Decimal(Decimal::from_str(s).unwrap())
+}
+
+fn bigint(value: i64) -> Number {
+    Number::BigInt(BigInt::from(value))
+}
+
+#[test]
+fn decimal_ops_do_not_round() {
+    assert_eq!(decimal("0.1").exact_op(&MathOp::Add, &decimal("0.2")), Some(decimal("0.3")));
+    assert_eq!(decimal("1").exact_op(&MathOp::Div, &decimal("4")), Some(decimal("0.25")));
+    assert_eq!(decimal("1.5").exact_op(&MathOp::Pow, &decimal("3")), Some(decimal("3.375")));
+    assert_eq!(decimal("2").exact_op(&MathOp::Pow, &decimal("-2")), Some(decimal("0.25")));
+    assert_eq!(decimal("2").exact_op(&MathOp::Pow, &decimal("0.5")), None);
+    assert_eq!(decimal("1").exact_op(&MathOp::Div, &decimal("0")), None);
+}
+
+#[test]
+fn bigint_ops_do_not_overflow() {
+    let max = Number::BigInt(BigInt::from(u128::MAX));
+
+    assert_eq!(max.exact_op(&MathOp::Add, &bigint(1)), Some(Number::BigInt(BigInt::from(1u8) << 128)));
+    assert_eq!(bigint(2).exact_op(&MathOp::Pow, &bigint(200)), Some(Number::BigInt(BigInt::from(1u8) << 200)));
+    assert_eq!(bigint(-7).exact_op(&MathOp::Mod, &bigint(3)), Some(bigint(-1)));
+    assert_eq!(bigint(1).exact_op(&MathOp::Mod, &bigint(0)), None);
+    assert_eq!(bigint(2).exact_op(&MathOp::Pow, &bigint(-1)), None);
+}
+
+#[test]
+fn huge_powers_are_refused() {
+    // refused without looping or allocating for the size of the exponent
+    assert_eq!(bigint(10).exact_op(&MathOp::Pow, &bigint(u32::MAX as i64)), None);
+    assert_eq!(decimal("1.1").exact_op(&MathOp::Pow, &decimal("4000000000")), None);
+    assert_eq!(bigint(-1).exact_op(&MathOp::Pow, &bigint(u32::MAX as i64)), Some(bigint(-1)));
+    assert_eq!(decimal("1").exact_op(&MathOp::Pow, &decimal("4000000000")), Some(decimal("1")));
+}
+
+#[test]
+fn mixed_types_have_no_exact_op() {
+    assert_eq!(bigint(1).exact_op(&MathOp::Add, &decimal("1")), None);
+    assert_eq!(Number::F64(0.1).exact_op(&MathOp::Add, &Number::F64(0.2)), None);
+}
@@ -0,0 +1,50 @@
+mod common;
+
+use props_parser::error::ParserErr;
+use common::errs;
+
+const PAIR: &str = "type Pair(T, U) = (T, U)\n";
+
+#[test]
+fn generic_type_instantiated_with_its_arguments() {
+    assert!(errs(&format!("{}p: Pair(I32, Str) = 1, \"s\"\nq: (I32, Str) = p\n", PAIR)).is_empty());
+
+    let errs = errs(&format!("{}p: Pair(I32, Str) = \"s\", 1\n", PAIR));
+    assert!(matches!(errs.as_slice(), [ParserErr::UnmatchedTypes { .. }]), "{:?}", errs);
+    assert_eq!(errs[0].to_string(), "Can not assign type (Str, I16) to an identifier of type Pair(I32, Str)");
+}
+
+#[test]
+fn type_arg_count() {
+    for (source, expected, found) in [
+        (format!("{}p: Pair(I32) = 1, 2\n", PAIR), 2, 1),
+        (format!("{}p: Pair(I32, Str, I32) = 1, \"s\", 2\n", PAIR), 2, 3),
+        ("type Meters = F64\nm: Meters(F64) = 1.5\n".to_string(), 0, 1),
+    ] {
+        let errs = errs(&source);
+        assert!(
+            matches!(errs.as_slice(), [ParserErr::TypeArgCount { expected: e, found: f, .. }] if *e == expected && *f == found),
+            "{}: {:?}", source, errs,
+        );
+    }
+}
+
+#[test]
+fn generic_function_instantiated_per_call() {
+    let identity = "identity: (T) -> T = |x| x\n";
+
+    assert!(errs(&format!("{}a: I32 = identity 5\nb: Str = identity \"s\"\n", identity)).is_empty());
+
+    let errs = errs(&format!("{}s: Str = identity 5\n", identity));
+    assert!(matches!(errs.as_slice(), [ParserErr::UnmatchedTypes { .. }]), "{:?}", errs);
+}
+
+#[test]
+fn generic_function_over_generic_type() {
+    let first = format!("{}first: (Pair(T, U)) -> T = |p| {{\n\ta, b = p\n\treturn a\n}}\nn: I32 = 1\n", PAIR);
+
+    assert!(errs(&format!("{}k: I32 = first (n, \"s\")\n", first)).is_empty());
+
+    let errs = errs(&format!("{}k: Str = first (n, \"s\")\n", first));
+    assert!(matches!(errs.as_slice(), [ParserErr::UnmatchedTypes { .. }]), "{:?}", errs);
+}
@@ -0,0 +1,45 @@
+mod common;
+
+use props_parser::error::ParserErr;
+use props_parser::nodes::Span;
+use common::errs;
+
+#[test]
+fn mutable_reassignment() {
+    assert!(errs("mut x = 1\nx = 2\nmut y: I64 = 1\nn: I32 = 2\ny = n\n").is_empty());
+}
+
+#[test]
+fn immutable_reassignment() {
+    let errs = errs("x = 1\nx = 2\n");
+
+    assert!(
+        matches!(errs.as_slice(), [ParserErr::ImmutableReassignment { span, previous, .. }]
+            if *span == Span::new(1, 0, 1) && *previous == Span::new(0, 0, 1)),
+        "{:?}", errs,
+    );
+}
+
+#[test]
+fn reassigned_type() {
+    let errs = errs("mut x: I32 = 1\nx = \"s\"\n");
+
+    assert!(
+        matches!(errs.as_slice(), [ParserErr::ReassignedType { span, previous, .. }]
+            if *span == Span::new(1, 0, 1) && *previous == Span::new(0, 4, 5)),
+        "{:?}", errs,
+    );
+    assert_eq!(errs[0].to_string(), "Can not reassign x of type I32 with a value of type Str");
+}
+
+#[test]
+fn reassignment_keeps_inferred_type() {
+    let errs = errs("mut x = 1\nx = \"s\"\n");
+
+    assert!(matches!(errs.as_slice(), [ParserErr::ReassignedType { .. }]), "{:?}", errs);
+}
+
+#[test]
+fn shadowing_in_inner_scope() {
+    assert!(errs("x = 1\nf = |a: I32| {\n\tx = \"s\"\n\treturn a\n}\n").is_empty());
+}
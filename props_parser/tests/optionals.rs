@@ -0,0 +1,35 @@
+mod common;
+
+use props_parser::error::ParserErr;
+use common::errs;
+
+#[test]
+fn value_assignable_to_optional() {
+    assert!(errs("x: I32? = none\nn: I32 = 1\ny: I32? = n\nz: I64? = y\n").is_empty());
+}
+
+#[test]
+fn unwrapped_optional() {
+    for source in ["x: I32? = none\ny: I32 = x\n", "x: I32? = 1\ny = x + 1\n", "x = none\ny: I32 = x\n"] {
+        let errs = errs(source);
+        assert!(matches!(errs.as_slice(), [ParserErr::UnwrappedOptional { .. }]), "{}: {:?}", source, errs);
+    }
+}
+
+#[test]
+fn coalesce_unwraps() {
+    assert!(errs("x: I32? = none\ny: I32 = x ?? 0\nz: I32 = (x ?? 0) + 1\n").is_empty());
+
+    let errs = errs("x: I32? = none\ny = x ?? \"s\"\n");
+    assert!(matches!(errs.as_slice(), [ParserErr::UnmatchedTypes { .. }]), "{:?}", errs);
+}
+
+#[test]
+fn safe_access_stays_optional() {
+    let show = "trait Show {\n\tshow: (Self) -> I32\n}\nimpl Show for I32 {\n\tshow = |x| x\n}\nx: I32? = 1\n";
+
+    assert!(errs(&format!("{}y: I32? = x?.show\nz: I32 = x?.show ?? 0\n", show)).is_empty());
+
+    let errs = errs(&format!("{}y: I32 = x?.show\n", show));
+    assert!(matches!(errs.as_slice(), [ParserErr::UnwrappedOptional { .. }]), "{:?}", errs);
+}
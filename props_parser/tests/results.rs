@@ -0,0 +1,53 @@
+mod common;
+
+use props_parser::error::ParserErr;
+use common::errs;
+
+const PARSE: &str = "parse: (Str) -> Result(I32, Str) = |s| {\n\treturn err \"bad\"\n}\n";
+
+#[test]
+fn propagation_takes_the_value_type() {
+    let source = format!("{}f = |s: Str| {{\n\tn = (parse s)?\n\treturn ok n\n}}\nr: Result(I32, Str) = f \"1\"\n", PARSE);
+    let errs = errs(&source);
+
+    assert!(errs.is_empty(), "{:?}", errs);
+}
+
+#[test]
+fn propagated_error_type() {
+    let source = format!("{}f = |s: Str| {{\n\tn = (parse s)?\n\treturn err 1\n}}\n", PARSE);
+    let errs = errs(&source);
+
+    assert!(matches!(errs.as_slice(), [ParserErr::PropagatedErrorType { .. }]), "{:?}", errs);
+    assert_eq!(errs[0].to_string(), "Can not propagate error type Str from a function returning errors of type I16");
+}
+
+#[test]
+fn invalid_propagation() {
+    let source = format!("{}f = |s: Str| {{\n\tn = (parse s)?\n\treturn n\n}}\n", PARSE);
+    let errs = errs(&source);
+
+    assert!(matches!(errs.as_slice(), [ParserErr::InvalidPropagation { .. }]), "{:?}", errs);
+}
+
+#[test]
+fn propagation_outside_function() {
+    let errs = errs(&format!("{}n = (parse \"1\")?\n", PARSE));
+
+    assert!(matches!(errs.as_slice(), [ParserErr::PropagationOutsideFunction { .. }]), "{:?}", errs);
+}
+
+#[test]
+fn not_result() {
+    let errs = errs("f = |s: I32| {\n\tn = s?\n\treturn ok n\n}\n");
+
+    assert!(matches!(errs.as_slice(), [ParserErr::NotResult { .. }]), "{:?}", errs);
+}
+
+#[test]
+fn constructors_fill_in_their_side() {
+    assert!(errs("a: Result(I32, Str) = ok 1\nb: Result(I32, Str) = err \"e\"\n").is_empty());
+
+    let errs = errs("a: Result(I32, Str) = err 1\n");
+    assert!(matches!(errs.as_slice(), [ParserErr::UnmatchedTypes { .. }]), "{:?}", errs);
+}
@@ -0,0 +1,59 @@
+mod common;
+
+use props_parser::error::ParserErr;
+use common::errs;
+
+const SHOW: &str = "trait Show {\n\tshow: (Self) -> I32\n}\n";
+const SHOW_I32: &str = "impl Show for I32 {\n\tshow = |x| x\n}\n";
+
+#[test]
+fn method_called_through_accessor() {
+    let errs = errs(&format!("{}{}n: I32 = 1\nk: I32 = n.show\n", SHOW, SHOW_I32));
+
+    assert!(errs.is_empty(), "{:?}", errs);
+}
+
+#[test]
+fn method_of_user_defined_type() {
+    let impl_point = "impl Show for Point {\n\tshow = |p| {\n\t\tx, y = p\n\t\treturn x\n\t}\n}\n";
+    let errs = errs(&format!("{}type Point = (I32, I32)\n{}p: Point = 1, 2\nn: I32 = p.show\n", SHOW, impl_point));
+
+    assert!(errs.is_empty(), "{:?}", errs);
+}
+
+#[test]
+fn missing_method() {
+    let source = "trait Show {\n\tshow: (Self) -> I32\n\ttwice: (Self) -> I32\n}\n";
+    let errs = errs(&format!("{}{}", source, SHOW_I32));
+
+    assert!(matches!(errs.as_slice(), [ParserErr::MissingMethod { method, .. }] if method == "twice"), "{:?}", errs);
+}
+
+#[test]
+fn unknown_method() {
+    let errs = errs(&format!("{}impl Show for I32 {{\n\tshow = |x| x\n\tother = |x| x\n}}\n", SHOW));
+
+    assert!(matches!(errs.as_slice(), [ParserErr::UnknownMethod { method, .. }] if method == "other"), "{:?}", errs);
+}
+
+#[test]
+fn conflicting_impl() {
+    let errs = errs(&format!("{}{}{}", SHOW, SHOW_I32, SHOW_I32));
+
+    assert!(matches!(errs.as_slice(), [ParserErr::ConflictingImpl { .. }]), "{:?}", errs);
+}
+
+#[test]
+fn missing_impl() {
+    let errs = errs(&format!("{}{}s: Str = \"s\"\nn = s.show\n", SHOW, SHOW_I32));
+
+    assert!(matches!(errs.as_slice(), [ParserErr::MissingImpl { .. }]), "{:?}", errs);
+    assert_eq!(errs[0].to_string(), "Type Str does not implement a trait with method show");
+}
+
+#[test]
+fn undefined_trait() {
+    let errs = errs(SHOW_I32);
+
+    assert!(matches!(errs.as_slice(), [ParserErr::UndefinedTrait { .. }]), "{:?}", errs);
+}
@@ -0,0 +1,48 @@
+mod common;
+
+use props_parser::error::ParserErr;
+use props_parser::pipeline::PropsPipeline;
+use props_parser::types::{PrimitiveType, Type};
+use common::errs;
+
+/** The annotation of a binding, displayed **/
+fn display(annotation: &str) -> String {
+    let mut pipeline = PropsPipeline::default();
+    pipeline.parse(format!("type Pair(T, U) = (T, U)\nx: {} = none\n", annotation));
+
+    let symbol = pipeline.symbols().symbols().iter().find(|symbol| symbol.name == "x").unwrap();
+    symbol.annotation.as_ref().unwrap().to_string()
+}
+
+#[test]
+fn types_display_as_written() {
+    for annotation in [
+        "I32",
+        "Str?",
+        "(I32, Str)",
+        "(I32, (F64, Char))",
+        "(I32, Str) -> Str",
+        "() -> Unit",
+        "impure (Str) -> Result(I32, Str)",
+        "Pair(I32, Str)",
+        "Pair(I32, Str)?",
+        "Option((I32) -> I32)",
+    ] {
+        assert_eq!(display(annotation), annotation);
+    }
+}
+
+#[test]
+fn unknown_types_display_as_placeholder() {
+    assert_eq!(Type::Undefined.to_string(), "_");
+    assert_eq!(Type::Optional(Box::new(Type::Undefined)).to_string(), "_?");
+    assert_eq!(Type::Optional(Box::new(Type::Primitive(PrimitiveType::I32))).to_string(), "I32?");
+}
+
+#[test]
+fn diagnostics_display_types() {
+    let errs = errs("x: (Str, I32) = 1, \"s\"\n");
+
+    assert!(matches!(errs.as_slice(), [ParserErr::UnmatchedTypes { .. }]), "{:?}", errs);
+    assert_eq!(errs[0].to_string(), "Can not assign type (I16, Str) to an identifier of type (Str, I32)");
+}
@@ -0,0 +1,25 @@
+mod common;
+
+use props_parser::error::ParserErr;
+use common::{errs, type_of};
+
+const SET: &str = "set = |s: I32| {\n\tn = s\n}\n";
+
+#[test]
+fn function_without_return_returns_unit() {
+    assert!(errs(SET).is_empty());
+    assert_eq!(type_of(SET, "set"), "(I32) -> Unit");
+}
+
+#[test]
+fn unit_call_as_statement() {
+    assert!(errs(&format!("{}set 1\n", SET)).is_empty());
+}
+
+#[test]
+fn unit_value() {
+    for source in [format!("{}y = (set 1) + 2\n", SET), format!("{}y: I32 = 2 * (set 1)\n", SET)] {
+        let errs = errs(&source);
+        assert!(matches!(errs.as_slice(), [ParserErr::UnitValue { .. }]), "{}: {:?}", source, errs);
+    }
+}
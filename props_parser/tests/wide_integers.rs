@@ -0,0 +1,59 @@
+mod common;
+
+use props_parser::error::ParserErr;
+use common::errs;
+
+#[test]
+fn operation_takes_widened_operand_type() {
+    assert!(errs("a: U8 = 1\nb: U16 = 2\nc: U16 = a + b\n").is_empty());
+    assert!(errs("a: I32 = 1\nb: I128 = 2\nc: I128 = b - a\n").is_empty());
+    assert!(errs("a: U64 = 1\nb: U128 = 2\nc: U128 = a * b\n").is_empty());
+
+    let errs = errs("a: U8 = 1\nb: U16 = 2\nc: U8 = a + b\n");
+    assert!(matches!(errs.as_slice(), [ParserErr::UnmatchedTypes { .. }]), "{:?}", errs);
+}
+
+#[test]
+fn literal_operand_takes_other_type() {
+    assert!(errs("a: U64 = 1\nb: U64 = a + 1\nc: U128 = 2 * (b as U128)\n").is_empty());
+    assert!(errs("a: F32 = 1.5\nb: F32 = a * 2\n").is_empty());
+}
+
+#[test]
+fn u128_and_i128_do_not_mix() {
+    let errs = errs("a: U128 = 1\nb: I128 = 2\nc: Str = a + b\n");
+
+    assert!(matches!(errs.as_slice(), [ParserErr::IncompatibleOperands { .. }]), "{:?}", errs);
+}
+
+#[test]
+fn float_and_i128_do_not_mix() {
+    let errs = errs("a: F64 = 1.5\nb: I128 = 2\nc: I128 = a + b\n");
+
+    assert!(matches!(errs.as_slice(), [ParserErr::IncompatibleOperands { .. }]), "{:?}", errs);
+}
+
+#[test]
+fn char_is_not_numeric() {
+    let errs = errs("a: Char = 'a'\nb: I32 = 1\nc = a + b\n");
+
+    assert!(matches!(errs.as_slice(), [ParserErr::IncompatibleOperands { .. }]), "{:?}", errs);
+}
+
+#[test]
+fn in_range_literal_keeps_narrow_type() {
+    for source in [
+        "a: U8 = 1\nb: U8 = a + 1\n",
+        "a: U8 = 1\nb: U8 = 255 - a\n",
+        "a: U16 = 1\nb: U16 = a * 300\n",
+        "a: U32 = 1\nb: U32 = 70000 + a\n",
+        "c: I32 = 5\nd: I32 = c + 100000\n",
+        "c: I32 = 5\nd: I32 = -100000 * c\n",
+    ] {
+        let errs = errs(source);
+        assert!(errs.is_empty(), "{}: {:?}", source, errs);
+    }
+
+    let errs = errs("a: U8 = 1\nb: U8 = a + 300\n");
+    assert!(matches!(errs.as_slice(), [ParserErr::LiteralOutOfRange { .. }]), "{:?}", errs);
+}